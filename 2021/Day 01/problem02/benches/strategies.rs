@@ -0,0 +1,67 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use expense_report::{Query, Strategy};
+
+/// Generates `size` odd entries plus a single planted pair adding up to the returned target.
+///
+/// Two odd entries never add up to the odd target, so every strategy has to scan the whole input.
+fn generate(size: usize) -> (Vec<usize>, usize) {
+    let target = 20 * size + 1;
+    let mut state: usize = 0x2545_f491;
+    let mut numbers: Vec<usize> = (0..size - 2)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            ((state >> 33) % (target / 2)) | 1
+        })
+        .collect();
+    numbers.push(2 * size);
+    numbers.push(target - 2 * size);
+
+    (numbers, target)
+}
+
+fn pair_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pair");
+    group.sample_size(10);
+
+    for exponent in 3..=7 {
+        let size = 10usize.pow(exponent);
+        let (numbers, target) = generate(size);
+
+        for strategy in Strategy::ALL.iter() {
+            let query = Query::new(2, target).with_strategy(*strategy);
+            group.bench_with_input(
+                BenchmarkId::new(strategy.name(), size),
+                &numbers,
+                |b, numbers| b.iter(|| query.find(numbers).expect("planted pair")),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn triple_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("triple");
+    group.sample_size(10);
+
+    for exponent in 3..=4 {
+        let size = 10usize.pow(exponent);
+        let (numbers, target) = generate(size);
+
+        for strategy in Strategy::ALL.iter() {
+            let query = Query::new(3, target + 1).with_strategy(*strategy);
+            group.bench_with_input(
+                BenchmarkId::new(strategy.name(), size),
+                &numbers,
+                |b, numbers| b.iter(|| query.find(numbers)),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, pair_strategies, triple_strategies);
+criterion_main!(benches);
@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Integer type an expense report can be kept in.
+///
+/// All arithmetic of the solver is checked, a sum that does not fit into the type never matches.
+pub trait Amount:
+    Copy + Ord + Hash + Debug + Display + FromStr<Err = ParseIntError> + Send + Sync + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    /// Wrapping sum, together with whether it wrapped around.
+    fn overflowing_add(self, other: Self) -> (Self, bool);
+
+    /// Absolute difference, wide enough for any pair of amounts.
+    fn distance(self, other: Self) -> u128;
+}
+
+macro_rules! amount {
+    ($($t:ty),*) => {
+        $(
+            impl Amount for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                fn overflowing_add(self, other: Self) -> (Self, bool) {
+                    <$t>::overflowing_add(self, other)
+                }
+
+                fn distance(self, other: Self) -> u128 {
+                    self.abs_diff(other) as u128
+                }
+            }
+        )*
+    };
+}
+
+amount!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+pub fn checked_sum<T: Amount>(amounts: impl IntoIterator<Item = T>) -> Option<T> {
+    amounts
+        .into_iter()
+        .try_fold(T::ZERO, |sum, amount| sum.checked_add(amount))
+}
+
+pub fn checked_product<T: Amount>(amounts: impl IntoIterator<Item = T>) -> Option<T> {
+    amounts
+        .into_iter()
+        .try_fold(T::ONE, |product, amount| product.checked_mul(amount))
+}
+
+/// `target` minus the sum of `terms`, even where partial sums do not fit into `T`.
+///
+/// Terms are subtracted in an order that keeps the difference within bounds for as long as
+/// possible, so it only overflows if the result does not fit either. Then the error tells how the
+/// sum of `terms` compares with `target`.
+pub(crate) fn remainder<T, I>(target: T, terms: I) -> Result<T, Ordering>
+where
+    T: Amount,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: Clone,
+{
+    let terms = terms.into_iter();
+    let mut positive = terms.clone().filter(|term| *term >= T::ZERO);
+    let mut negative = terms.filter(|term| *term < T::ZERO);
+    let mut rest = target;
+
+    loop {
+        let term = if rest >= T::ZERO {
+            positive.next().or_else(|| negative.next())
+        } else {
+            negative.next().or_else(|| positive.next())
+        };
+        match term {
+            None => return Ok(rest),
+            Some(term) => match rest.checked_sub(term) {
+                Some(difference) => rest = difference,
+                None if term > T::ZERO => return Err(Ordering::Greater),
+                None => return Err(Ordering::Less),
+            },
+        }
+    }
+}
+
+/// Sum of `amounts`, even where partial sums do not fit into `T`.
+///
+/// Amounts are added in an order that keeps the sum within bounds for as long as possible. If the
+/// sum does not fit either, the error tells on which side of the range of `T` it lies.
+pub fn total<T, I>(amounts: I) -> Result<T, Ordering>
+where
+    T: Amount,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: Clone,
+{
+    let amounts = amounts.into_iter();
+    let mut positive = amounts.clone().filter(|amount| *amount >= T::ZERO);
+    let mut negative = amounts.filter(|amount| *amount < T::ZERO);
+    let mut sum = T::ZERO;
+
+    loop {
+        let amount = if sum >= T::ZERO {
+            negative.next().or_else(|| positive.next())
+        } else {
+            positive.next().or_else(|| negative.next())
+        };
+        match amount {
+            None => return Ok(sum),
+            Some(amount) => match sum.checked_add(amount) {
+                Some(next) => sum = next,
+                None if amount > T::ZERO => return Err(Ordering::Greater),
+                None => return Err(Ordering::Less),
+            },
+        }
+    }
+}
+
+/// Compares the sum of `terms` with `target`, even where the sum does not fit into `T`.
+pub(crate) fn compare_total<T, I>(terms: I, target: T) -> Ordering
+where
+    T: Amount,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: Clone,
+{
+    match total(terms) {
+        Ok(sum) => sum.cmp(&target),
+        Err(ordering) => ordering,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amount::{checked_product, checked_sum, compare_total, remainder, total, Amount};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn should_sum_signed_amounts() {
+        assert_eq!(checked_sum(vec![-5i64, 12, -7]), Some(0))
+    }
+
+    #[test]
+    fn should_measure_distance_across_the_whole_range() {
+        assert_eq!(Amount::distance(i128::MIN, i128::MAX), u128::MAX);
+        assert_eq!(Amount::distance(-3i64, 4), 7)
+    }
+
+    #[test]
+    fn should_detect_overflowing_sums() {
+        assert_eq!(checked_sum(vec![u128::MAX, 1]), None);
+        assert_eq!(checked_product(vec![i64::MAX, 2]), None)
+    }
+
+    #[test]
+    fn should_subtract_terms_whose_partial_sums_overflow() {
+        assert_eq!(remainder(i64::MAX - 5, [20, i64::MAX - 10, -15]), Ok(0));
+        assert_eq!(remainder(120i8, [100, 60]), Ok(-40));
+        assert_eq!(
+            remainder(i8::MIN, [i8::MAX, i8::MAX]),
+            Err(Ordering::Greater)
+        );
+        assert_eq!(remainder(2u8, [1, 3]), Err(Ordering::Greater));
+        assert_eq!(remainder(i8::MAX, [-1]), Err(Ordering::Less))
+    }
+
+    #[test]
+    fn should_add_amounts_whose_partial_sums_overflow() {
+        assert_eq!(total([i64::MIN, -1, 100]), Ok(i64::MIN + 99));
+        assert_eq!(total([100i8, 60, -40]), Ok(120));
+        assert_eq!(total([i8::MIN, -1]), Err(Ordering::Less));
+        assert_eq!(total([u8::MAX, 1]), Err(Ordering::Greater))
+    }
+
+    #[test]
+    fn should_compare_totals_beyond_the_bounds() {
+        assert_eq!(compare_total([100i8, 60, -40], 120), Ordering::Equal);
+        assert_eq!(compare_total([100i8, 60, -41], 120), Ordering::Less);
+        assert_eq!(compare_total([i8::MIN, i8::MIN, 127], 0), Ordering::Less);
+        assert_eq!(compare_total([u128::MAX, 1], u128::MAX), Ordering::Greater)
+    }
+}
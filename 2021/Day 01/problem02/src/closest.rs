@@ -0,0 +1,206 @@
+use crate::amount::{total, Amount};
+use crate::search::{Query, SearchResult, Sorted};
+use std::cmp::Ordering;
+
+/// The tuple whose sum is nearest to the target of a query.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Closest<T> {
+    pub result: SearchResult<T>,
+    pub sum: T,
+    pub distance: u128,
+}
+
+/// Which sums a candidate may have.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Fit {
+    Nearest,
+    /// Only sums up to the target, the nearest being the largest.
+    AtMost,
+}
+
+/// Best candidate so far, as positions into the sorted entries.
+struct Candidate<T> {
+    positions: Vec<usize>,
+    sum: T,
+    distance: u128,
+}
+
+pub(crate) fn closest<T: Amount>(numbers: &[T], query: &Query<T>, fit: Fit) -> Option<Closest<T>> {
+    let sorted = Sorted::new(numbers);
+    let mut best: Option<Candidate<T>> = None;
+    let mut prefix = Vec::with_capacity(query.k);
+    let mut search = Search {
+        sorted: &sorted,
+        query,
+        fit,
+        best: &mut best,
+    };
+
+    match query.k {
+        0 => search.consider(&prefix, T::ZERO),
+        1 => {
+            for (position, value) in sorted.values.iter().enumerate() {
+                search.consider(&[position], *value);
+            }
+        }
+        _ => search.descend(query.k, 0, &mut prefix),
+    }
+
+    best.map(|candidate| {
+        let mut indices: Vec<usize> = candidate
+            .positions
+            .iter()
+            .map(|position| sorted.order[*position])
+            .collect();
+        indices.sort_by_key(|index| (numbers[*index], *index));
+
+        Closest {
+            result: SearchResult::new(numbers, query.target, indices),
+            sum: candidate.sum,
+            distance: candidate.distance,
+        }
+    })
+}
+
+/// State of one search, shared by every level of the descent.
+struct Search<'a, T> {
+    sorted: &'a Sorted<T>,
+    query: &'a Query<T>,
+    fit: Fit,
+    best: &'a mut Option<Candidate<T>>,
+}
+
+impl<T: Amount> Search<'_, T> {
+    /// Picks the entries before the last pair, keeping their positions rather than their sum,
+    /// which may not fit into `T` even where the sum of all entries does.
+    fn descend(&mut self, k: usize, start: usize, prefix: &mut Vec<usize>) {
+        if k == 2 {
+            return self.sweep(start, prefix);
+        }
+
+        for position in start..self.sorted.values.len() {
+            let next = if self.query.with_replacement {
+                position
+            } else {
+                position + 1
+            };
+
+            prefix.push(position);
+            self.descend(k - 1, next, prefix);
+            prefix.pop();
+        }
+    }
+
+    /// Two pointer sweep over the last pair, tracking the sum nearest to the target.
+    fn sweep(&mut self, start: usize, prefix: &mut Vec<usize>) {
+        let values = &self.sorted.values;
+        if start >= values.len() {
+            return;
+        }
+
+        let (mut low, mut high) = (start, values.len() - 1);
+        while low < high || (self.query.with_replacement && low == high) {
+            prefix.extend_from_slice(&[low, high]);
+            let ordering = match total(prefix.iter().map(|position| values[*position])) {
+                Ok(sum) => {
+                    self.consider(prefix, sum);
+                    sum.cmp(&self.query.target)
+                }
+                Err(ordering) => ordering,
+            };
+            prefix.truncate(prefix.len() - 2);
+
+            match ordering {
+                Ordering::Equal => return,
+                Ordering::Less => low += 1,
+                Ordering::Greater if high == 0 => break,
+                Ordering::Greater => high -= 1,
+            }
+        }
+    }
+
+    fn consider(&mut self, positions: &[usize], sum: T) {
+        if self.fit == Fit::AtMost && sum > self.query.target {
+            return;
+        }
+
+        let distance = sum.distance(self.query.target);
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| distance < best.distance)
+        {
+            *self.best = Some(Candidate {
+                positions: positions.to_vec(),
+                sum,
+                distance,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::Query;
+
+    #[test]
+    fn should_report_exact_match_with_zero_distance() {
+        let numbers = vec![1721, 979, 366, 299, 675, 1456];
+        let closest = Query::new(2, 2020).closest(&numbers).unwrap();
+
+        assert_eq!(closest.result.entries, vec![299, 1721]);
+        assert_eq!(closest.distance, 0)
+    }
+
+    #[test]
+    fn should_find_nearest_pair() {
+        let numbers = vec![1000, 500, 1013, 2];
+        let closest = Query::new(2, 2020).closest(&numbers).unwrap();
+
+        assert_eq!(closest.result.entries, vec![1000, 1013]);
+        assert_eq!(closest.sum, 2013);
+        assert_eq!(closest.distance, 7)
+    }
+
+    #[test]
+    fn should_find_nearest_triple_above_target() {
+        let numbers: Vec<i64> = vec![-50, 900, 1200, 3000];
+        let closest = Query::new(3, 2020).closest(&numbers).unwrap();
+
+        assert_eq!(closest.result.entries, vec![-50, 900, 1200]);
+        assert_eq!(closest.distance, 30)
+    }
+
+    #[test]
+    fn should_respect_replacement() {
+        let numbers = vec![1000, 7];
+        let distinct = Query::new(2, 2000).closest(&numbers).unwrap();
+        let replaced = Query::new(2, 2000)
+            .with_replacement(true)
+            .closest(&numbers)
+            .unwrap();
+
+        assert_eq!(distinct.distance, 993);
+        assert_eq!(replaced.distance, 0)
+    }
+
+    #[test]
+    fn should_match_exactly_where_partial_sums_overflow() {
+        let numbers: Vec<i64> = vec![100, i64::MIN, -1];
+        let query = Query::new(3, i64::MIN + 99);
+
+        for best in [query.closest(&numbers), query.at_most(&numbers)] {
+            let best = best.unwrap();
+
+            assert_eq!(best.result.entries, vec![i64::MIN, -1, 100]);
+            assert_eq!(best.distance, 0)
+        }
+    }
+
+    #[test]
+    fn should_have_nothing_without_enough_entries() {
+        let numbers = vec![2020];
+
+        assert_eq!(Query::new(2, 2020).closest(&numbers), None)
+    }
+}
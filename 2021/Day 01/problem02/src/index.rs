@@ -0,0 +1,203 @@
+use crate::amount::Amount;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Expense entries kept across changes, answering pair queries without rescanning the report.
+///
+/// Entries are counted per value, in no particular order. Targets registered with
+/// [`ExpenseIndex::watch`] keep their number of matching pairs up to date on every insert and
+/// remove, making [`ExpenseIndex::has_pair`] and [`ExpenseIndex::pair_count`] constant time for
+/// them. Every other query, and [`ExpenseIndex::pair`] for a watched target that has a pair, scans
+/// all distinct values, so callers asking about the same target repeatedly should `watch` it.
+#[derive(Debug, Default, Clone)]
+pub struct ExpenseIndex<T> {
+    counts: HashMap<T, usize>,
+    len: usize,
+    watched: HashMap<T, usize>,
+}
+
+impl<T: Amount> ExpenseIndex<T> {
+    pub fn new() -> Self {
+        ExpenseIndex {
+            counts: HashMap::new(),
+            len: 0,
+            watched: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn count(&self, value: T) -> usize {
+        self.counts.get(&value).copied().unwrap_or(0)
+    }
+
+    /// Keeps track of the pairs adding up to `target` from now on.
+    pub fn watch(&mut self, target: T) {
+        let pairs = self.pair_count(target);
+        self.watched.insert(target, pairs);
+    }
+
+    pub fn insert(&mut self, value: T) {
+        for (target, pairs) in self.watched.iter_mut() {
+            if let Some(partner) = target.checked_sub(value) {
+                *pairs += self.counts.get(&partner).copied().unwrap_or(0);
+            }
+        }
+
+        *self.counts.entry(value).or_insert(0) += 1;
+        self.len += 1;
+    }
+
+    /// Removes one entry of `value`, `false` if there is none.
+    pub fn remove(&mut self, value: T) -> bool {
+        match self.counts.get_mut(&value) {
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.counts.remove(&value);
+            }
+            None => return false,
+        }
+        self.len -= 1;
+
+        for (target, pairs) in self.watched.iter_mut() {
+            if let Some(partner) = target.checked_sub(value) {
+                *pairs -= self.counts.get(&partner).copied().unwrap_or(0);
+            }
+        }
+        true
+    }
+
+    /// Whether two distinct entries still add up to `target`.
+    pub fn has_pair(&self, target: T) -> bool {
+        match self.watched.get(&target) {
+            Some(pairs) => *pairs > 0,
+            None => self.pair(target).is_some(),
+        }
+    }
+
+    /// Values of two distinct entries adding up to `target`, the smaller one first.
+    pub fn pair(&self, target: T) -> Option<(T, T)> {
+        if self.watched.get(&target) == Some(&0) {
+            return None;
+        }
+
+        self.counts
+            .iter()
+            .filter_map(|(value, count)| {
+                let partner = target.checked_sub(*value)?;
+                let available = if partner == *value {
+                    *count > 1
+                } else {
+                    *value < partner && self.counts.contains_key(&partner)
+                };
+                available.then_some((*value, partner))
+            })
+            .min()
+    }
+
+    /// Number of pairs of distinct entries adding up to `target`.
+    pub fn pair_count(&self, target: T) -> usize {
+        if let Some(pairs) = self.watched.get(&target) {
+            return *pairs;
+        }
+
+        self.counts
+            .iter()
+            .filter_map(|(value, count)| {
+                let partner = target.checked_sub(*value)?;
+                match partner.cmp(value) {
+                    Ordering::Equal => Some(count * (count - 1) / 2),
+                    Ordering::Greater => Some(count * self.count(partner)),
+                    Ordering::Less => None,
+                }
+            })
+            .sum()
+    }
+}
+
+impl<T: Amount> FromIterator<T> for ExpenseIndex<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut index = ExpenseIndex::new();
+        for value in values {
+            index.insert(value);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::ExpenseIndex;
+    use crate::search::k_sum_matches;
+
+    #[test]
+    fn should_find_aoc_pair() {
+        let index: ExpenseIndex<i64> = vec![1721, 979, 366, 299, 675, 1456].into_iter().collect();
+
+        assert_eq!(index.pair(2020), Some((299, 1721)));
+        assert_eq!(index.pair_count(2020), 1)
+    }
+
+    #[test]
+    fn should_follow_inserts_and_removes() {
+        let mut index: ExpenseIndex<i64> = vec![1721, 979, 366].into_iter().collect();
+        index.watch(2020);
+
+        assert!(!index.has_pair(2020));
+        index.insert(299);
+        assert!(index.has_pair(2020));
+        assert!(index.remove(1721));
+        assert!(!index.has_pair(2020));
+        assert!(!index.remove(1721));
+        assert_eq!(index.len(), 3)
+    }
+
+    #[test]
+    fn should_need_two_entries_of_a_half() {
+        let mut index = ExpenseIndex::new();
+        index.watch(2020);
+        index.insert(1010);
+
+        assert!(!index.has_pair(2020));
+        assert_eq!(index.pair(2020), None);
+        index.insert(1010);
+        assert!(index.has_pair(2020));
+        assert_eq!(index.pair(2020), Some((1010, 1010)))
+    }
+
+    #[test]
+    fn should_keep_watched_counts_in_line_with_rescans() {
+        let mut index = ExpenseIndex::new();
+        let mut numbers: Vec<i64> = Vec::new();
+        for target in [-4, 0, 10, 25] {
+            index.watch(target);
+        }
+
+        for step in 0..200i64 {
+            let value = (step * 37 + 5) % 31 - 8;
+            if step % 3 == 2 {
+                let removed = numbers.iter().position(|number| *number == value);
+                assert_eq!(index.remove(value), removed.is_some());
+                if let Some(position) = removed {
+                    numbers.swap_remove(position);
+                }
+            } else {
+                index.insert(value);
+                numbers.push(value);
+            }
+
+            for target in [-4, 0, 10, 25, 7] {
+                let expected = k_sum_matches(&numbers, 2, target).count();
+                assert_eq!(index.pair_count(target), expected, "step {}", step);
+                assert_eq!(index.has_pair(target), expected > 0)
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+pub mod amount;
+pub mod closest;
+pub mod index;
+pub mod objective;
+mod parallel;
+pub mod parse;
+pub mod records;
+pub mod search;
+pub mod strategy;
+pub mod stream;
+pub mod subset;
+
+pub use amount::Amount;
+pub use closest::Closest;
+pub use index::ExpenseIndex;
+pub use objective::{optimize, Goal, Objective, Optimum, UnknownObjective};
+pub use parse::{parse_line, EntryError, ExpenseReport, InvalidEntry, ParseError, Strictness};
+pub use records::{Format, Record};
+pub use search::{
+    find_k_sum, find_matching_pair, find_matching_triple, k_sum_matches, Matches, Query,
+    SearchResult,
+};
+pub use strategy::{Strategy, UnknownStrategy};
+pub use stream::{first_pair, PairStream, Sighting, StreamError, StreamedPair};
+pub use subset::subset_sum;
+
+pub const TARGET: usize = 2020;
@@ -1,97 +1,422 @@
-use std::env;
-use std::fs;
+use clap::builder::RangedU64ValueParser;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use expense_report::{
+    first_pair, optimize, subset_sum, Amount, ExpenseReport, Goal, Matches, Objective, PairStream,
+    Query, SearchResult, Strategy, Strictness,
+};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process;
 
-const TARGET: usize = 2020;
+/// Opens the input file, `-` standing for stdin.
+fn open_input(input: &str) -> Box<dyn BufRead> {
+    match input {
+        "-" => Box::new(BufReader::new(io::stdin())),
+        _ => Box::new(BufReader::new(
+            File::open(input).expect("Could not read from file!"),
+        )),
+    }
+}
 
-fn get_numbers() -> Vec<usize> {
-    let args: Vec<String> = env::args().collect();
-    let input = args.get(1).expect("Missing commandline argument!");
+fn get_report<T: Amount>(input: &str, strictness: Strictness) -> ExpenseReport<T> {
+    let contents = match input {
+        "-" => {
+            let mut contents = String::new();
+            io::stdin()
+                .read_to_string(&mut contents)
+                .expect("Could not read from stdin!");
+            contents
+        }
+        _ => fs::read_to_string(input).expect("Could not read from file!"),
+    };
 
-    let contents = fs::read_to_string(input).expect("Could not read from file!");
+    match ExpenseReport::parse(&contents, strictness) {
+        Ok(report) => {
+            for entry in &report.skipped {
+                eprintln!("skipped {}", entry);
+            }
+            report
+        }
+        Err(error) => {
+            eprintln!("{}:", error);
+            for entry in &error.0 {
+                eprintln!("- {}", entry);
+            }
+            process::exit(1);
+        }
+    }
+}
 
-    const ZERO: usize = 0;
+fn main() {
+    let matches = Command::new("AOC Day 01")
+        .about("Finds expense report entries adding up to a target")
+        .arg(
+            Arg::new("file")
+                .default_value("-")
+                .help("input file, - for stdin"),
+        )
+        .arg(
+            Arg::new("k")
+                .short('k')
+                .long("k")
+                .value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+                .default_value("3")
+                .help("number of entries to combine"),
+        )
+        .arg(
+            Arg::new("target")
+                .short('t')
+                .long("target")
+                .allow_negative_numbers(true)
+                .default_value("2020")
+                .help("sum the entries need to add up to"),
+        )
+        .arg(
+            Arg::new("amount")
+                .long("amount")
+                .value_parser(["i64", "i128", "u64", "u128"])
+                .default_value("i64")
+                .help("integer type the entries are read as"),
+        )
+        .arg(
+            Arg::new("strategy")
+                .short('s')
+                .long("strategy")
+                .value_parser(|name: &str| name.parse::<Strategy>())
+                .default_value("binary-search")
+                .help("search algorithm: binary-search, hash-set or two-pointer"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("strategy")
+                .help("search on all cores, with the same results as binary-search"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_parser(value_parser!(usize))
+                .requires("parallel")
+                .help("number of threads for --parallel, defaults to one per core"),
+        )
+        .arg(
+            Arg::new("with-replacement")
+                .long("with-replacement")
+                .action(ArgAction::SetTrue)
+                .help("allow an entry to be used more than once"),
+        )
+        .arg(
+            Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("skip invalid entries instead of failing"),
+        )
+        .arg(
+            Arg::new("stream")
+                .long("stream")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "k", "strategy", "parallel", "subset", "closest", "at-most", "between", "maximize",
+                    "minimize", "all", "count",
+                ])
+                .help("read line by line and stop at the first pair adding up to the target"),
+        )
+        .arg(
+            Arg::new("subset")
+                .long("subset")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "closest",
+                    "at-most",
+                    "between",
+                    "maximize",
+                    "minimize",
+                    "all",
+                    "count",
+                    "with-replacement",
+                    "parallel",
+                ])
+                .help("find the smallest subset of any size adding up to the target"),
+        )
+        .arg(
+            Arg::new("closest")
+                .long("closest")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "at-most", "between", "maximize", "minimize", "all", "count", "parallel",
+                ])
+                .help("report the tuple whose sum is nearest to the target"),
+        )
+        .arg(
+            Arg::new("at-most")
+                .long("at-most")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["between", "maximize", "minimize", "all", "count", "parallel"])
+                .help("report the tuple with the largest sum not exceeding the target"),
+        )
+        .arg(
+            Arg::new("between")
+                .long("between")
+                .num_args(2)
+                .value_names(["LO", "HI"])
+                .allow_negative_numbers(true)
+                .conflicts_with_all(["target", "parallel"])
+                .help("print every tuple whose sum lies within LO..=HI"),
+        )
+        .arg(
+            Arg::new("maximize")
+                .long("maximize")
+                .value_parser(|name: &str| name.parse::<Objective>())
+                .conflicts_with_all(["minimize", "all", "count", "parallel"])
+                .help("pick the matching tuple with the largest product, sum-of-squares, spread or largest entry"),
+        )
+        .arg(
+            Arg::new("minimize")
+                .long("minimize")
+                .value_parser(|name: &str| name.parse::<Objective>())
+                .conflicts_with_all(["all", "count", "parallel"])
+                .help("pick the matching tuple with the smallest product, sum-of-squares, spread or largest entry"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["count", "parallel"])
+                .help("print every matching tuple"),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .action(ArgAction::SetTrue)
+                .help("print the number of matching tuples"),
+        )
+        .get_matches();
 
-    let mut numbers: Vec<usize> = contents
-        .split(char::is_whitespace)
-        .map(|s| s.parse::<usize>().unwrap_or(ZERO))
-        .filter(|x| x > &ZERO)
-        .filter(|x| x < &TARGET)
-        .collect();
+    match matches.get_one::<String>("amount").map(String::as_str) {
+        Some("i128") => run::<i128>(&matches),
+        Some("u64") => run::<u64>(&matches),
+        Some("u128") => run::<u128>(&matches),
+        _ => run::<i64>(&matches),
+    }
+}
 
-    numbers.sort_unstable();
+fn run<T: Amount>(matches: &ArgMatches) {
+    let input_file: &String = matches
+        .get_one::<String>("file")
+        .expect("'file' is required");
+    let k: usize = *matches.get_one::<usize>("k").expect("'k' has a default");
+    let target: T = parse_amount(
+        matches
+            .get_one::<String>("target")
+            .expect("'target' has a default"),
+    );
 
-    return numbers;
-}
+    let strategy: Strategy = *matches
+        .get_one::<Strategy>("strategy")
+        .expect("'strategy' has a default");
 
-#[derive(Debug)]
-struct SearchResult {
-    target: usize,
-    one: usize,
-    two: usize,
-    three: usize,
-}
+    let query = Query::new(k, target)
+        .with_replacement(matches.get_flag("with-replacement"))
+        .with_strategy(strategy);
 
-fn find_matching_triple(numbers: &Vec<usize>) -> SearchResult {
-    const START: SearchResult = SearchResult {
-        target: 0,
-        one: 0,
-        two: 0,
-        three: 0,
+    let strictness = if matches.get_flag("lenient") {
+        Strictness::Lenient
+    } else {
+        Strictness::Strict
     };
 
-    numbers.iter().fold(START, |acc, element| {
-        if acc.target == TARGET {
-            return acc;
+    if matches.get_flag("stream") {
+        let stream = PairStream::new(target).with_replacement(query.with_replacement);
+        let result = first_pair(open_input(input_file), stream, strictness, |invalid| {
+            eprintln!("skipped {}", invalid)
+        });
+
+        match result {
+            Ok(Some(pair)) => {
+                println!(
+                    "found pair {} (line {}) and {} (line {})",
+                    pair.first.value, pair.first.line, pair.second.value, pair.second.line
+                );
+                println!("multiplied {}", describe(pair.product()));
+            }
+            Ok(None) => no_solution(&Query::new(2, target)),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
         }
+        return;
+    }
 
-        let pot_second_target: usize = TARGET - element;
+    let parallel = matches.get_flag("parallel");
+    if let Some(threads) = matches.get_one::<usize>("threads") {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(*threads)
+            .build_global()
+            .expect("Could not set up thread pool!");
+    }
 
-        let result: SearchResult = find_matching_pair(numbers, pot_second_target);
+    let report: ExpenseReport<T> = get_report(input_file, strictness);
+    let numbers = &report.numbers;
 
-        match result.target {
-            0 => acc,
-            _ => SearchResult {
-                target: TARGET,
-                one: element.clone(),
-                two: result.two,
-                three: result.three,
-            },
-        }
-    })
-}
+    let between: Option<(T, T)> = matches.get_many::<String>("between").map(|mut bounds| {
+        let mut bound = || parse_amount(bounds.next().expect("'between' takes two values"));
+        (bound(), bound())
+    });
 
-fn find_matching_pair(numbers: &Vec<usize>, pair_target: usize) -> SearchResult {
-    const START: SearchResult = SearchResult {
-        target: 0,
-        one: 0,
-        two: 0,
-        three: 0,
+    let objective = match (
+        matches.get_one::<Objective>("maximize"),
+        matches.get_one::<Objective>("minimize"),
+    ) {
+        (Some(objective), _) => Some((*objective, Goal::Maximize)),
+        (_, Some(objective)) => Some((*objective, Goal::Minimize)),
+        _ => None,
     };
 
-    numbers.iter().fold(START, |acc, element| {
-        if element > &pair_target || acc.target == pair_target {
-            return acc;
+    if let Some((objective, goal)) = objective {
+        let tuples: Matches<T> = match between {
+            Some((lower, upper)) => query.between(numbers, lower, upper),
+            None => query.matches(numbers),
+        };
+        let optimum = optimize(numbers, target, tuples, objective, goal);
+
+        match optimum.best {
+            Some((best, score)) => {
+                println!("best entries {:?}", best);
+                name_records(&report, &best);
+                println!("{} {}", objective, score);
+                println!("multiplied {}", describe(best.product()));
+            }
+            None if optimum.considered == 0 => {
+                eprintln!("no tuple of {} entries matches", k);
+                process::exit(1);
+            }
+            None => println!("every candidate has a {} out of range", objective),
+        }
+        println!("considered {} candidate tuples", optimum.considered);
+        if optimum.overflowed > 0 {
+            println!(
+                "left out {} with a {} out of range",
+                optimum.overflowed, objective
+            );
         }
+    } else if let Some((lower, upper)) = between {
+        let tuples = query.between(numbers, lower, upper);
+        if matches.get_flag("count") {
+            println!("{}", tuples.count());
+            return;
+        }
+
+        let mut found = false;
+        for indices in tuples {
+            let matching = SearchResult::new(numbers, target, indices);
+            let sum = matching.sum().expect("sum is within range");
+            println!(
+                "{:?} sum {} multiplied {}",
+                matching.entries,
+                sum,
+                describe(matching.product())
+            );
+            name_records(&report, &matching);
+            found = true;
+        }
+        if !found {
+            eprintln!("no {} entries add up to {}..={}", k, lower, upper);
+            process::exit(1);
+        }
+    } else if matches.get_flag("count") && parallel {
+        println!("{}", query.par_count(numbers));
+    } else if matches.get_flag("count") {
+        println!("{}", query.matches(numbers).count());
+    } else if matches.get_flag("subset") {
+        let subset = match subset_sum(numbers, target) {
+            Some(subset) => subset,
+            None => {
+                eprintln!("no subset of entries adds up to {}", target);
+                process::exit(1);
+            }
+        };
 
-        let pot_right = pair_target - element;
+        println!(
+            "found subset of {} entries {:?}",
+            subset.entries.len(),
+            subset
+        );
+        name_records(&report, &subset);
+        println!("multiplied {}", describe(subset.product()));
+    } else if matches.get_flag("closest") {
+        let closest = query
+            .closest(numbers)
+            .unwrap_or_else(|| no_solution(&query));
 
-        match numbers.binary_search(&pot_right) {
-            Result::Ok(_) => SearchResult {
-                target: pair_target,
-                one: 0,
-                two: element.clone(),
-                three: pot_right,
-            },
-            Result::Err(_) => acc,
+        println!("closest entries {:?}", closest.result);
+        name_records(&report, &closest.result);
+        println!("sum {} at distance {}", closest.sum, closest.distance);
+        println!("multiplied {}", describe(closest.result.product()));
+    } else if matches.get_flag("at-most") {
+        let best = query.at_most(numbers).unwrap_or_else(|| {
+            eprintln!("no {} entries fit into a budget of {}", k, target);
+            process::exit(1);
+        });
+
+        println!("largest entries within budget {:?}", best.result);
+        name_records(&report, &best.result);
+        println!("sum {} leaving {}", best.sum, best.distance);
+        println!("multiplied {}", describe(best.result.product()));
+    } else if matches.get_flag("all") {
+        let mut found = false;
+        for indices in query.matches(numbers) {
+            let matching = SearchResult::new(numbers, target, indices);
+            println!("{:?} multiplied {}", matching, describe(matching.product()));
+            name_records(&report, &matching);
+            found = true;
+        }
+        if !found {
+            no_solution(&query);
+        }
+    } else {
+        let matching = if parallel {
+            query.par_find(numbers)
+        } else {
+            query.find(numbers)
         }
-    })
+        .unwrap_or_else(|| no_solution(&query));
+
+        println!("found matching entries {:?}", matching);
+        name_records(&report, &matching);
+        println!("multiplied {}", describe(matching.product()));
+    }
 }
 
-fn main() {
-    let numbers: Vec<usize> = get_numbers();
+/// Lists the records behind a result, if the report was read from CSV or TSV.
+fn name_records<T: Amount>(report: &ExpenseReport<T>, result: &SearchResult<T>) {
+    if report.records.is_empty() {
+        return;
+    }
+    for index in &result.indices {
+        println!("  {} {}", report.records[*index], report.numbers[*index]);
+    }
+}
 
-    let matching = find_matching_triple(&numbers);
+fn parse_amount<T: Amount>(amount: &str) -> T {
+    match amount.parse() {
+        Ok(amount) => amount,
+        Err(error) => {
+            eprintln!("invalid amount {:?}: {}", amount, error);
+            process::exit(1);
+        }
+    }
+}
+
+fn no_solution<T: Amount>(query: &Query<T>) -> ! {
+    eprintln!("no {} entries add up to {}", query.k, query.target);
+    process::exit(1);
+}
 
-    println!("found matching triple {:?}", matching);
-    println!("multiplied {:?}", matching.one * matching.two * matching.three);
+fn describe<T: Amount>(product: Option<T>) -> String {
+    match product {
+        Some(product) => product.to_string(),
+        None => String::from("out of range"),
+    }
 }
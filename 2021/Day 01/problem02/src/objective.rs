@@ -0,0 +1,217 @@
+use crate::amount::{checked_product, Amount};
+use crate::search::SearchResult;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Score a matching tuple is ranked by when several tuples hit the target.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Objective {
+    /// Product of the entries.
+    Product,
+    /// Sum of the squared entries.
+    SumOfSquares,
+    /// Largest entry minus smallest entry.
+    Spread,
+    /// Largest entry.
+    Largest,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 4] = [
+        Objective::Product,
+        Objective::SumOfSquares,
+        Objective::Spread,
+        Objective::Largest,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Product => "product",
+            Objective::SumOfSquares => "sum-of-squares",
+            Objective::Spread => "spread",
+            Objective::Largest => "largest",
+        }
+    }
+
+    /// Names of all objectives, as listed in error messages.
+    fn names() -> String {
+        Objective::ALL.map(|objective| objective.name()).join(", ")
+    }
+
+    /// Scores `entries`, `None` if the score does not fit into `T`.
+    pub fn score<T: Amount>(&self, entries: &[T]) -> Option<T> {
+        let largest = entries.iter().max().copied();
+        match self {
+            Objective::Product => checked_product(entries.iter().copied()),
+            Objective::SumOfSquares => entries
+                .iter()
+                .map(|entry| entry.checked_mul(*entry))
+                .try_fold(T::ZERO, |sum, square| sum.checked_add(square?)),
+            Objective::Spread => largest?.checked_sub(*entries.iter().min()?),
+            Objective::Largest => largest,
+        }
+    }
+}
+
+impl Display for Objective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("unknown objective {0:?}, expected one of {}", Objective::names())]
+pub struct UnknownObjective(pub String);
+
+impl FromStr for Objective {
+    type Err = UnknownObjective;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Objective::ALL
+            .iter()
+            .find(|objective| objective.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownObjective(name.into()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Goal {
+    Maximize,
+    Minimize,
+}
+
+/// Outcome of ranking every candidate tuple.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Optimum<T> {
+    /// Best tuple with its score, the first one found on ties.
+    pub best: Option<(SearchResult<T>, T)>,
+    pub considered: usize,
+    /// Candidates left out because their score does not fit into `T`.
+    pub overflowed: usize,
+}
+
+/// Ranks the index tuples into `numbers` given by `tuples`, e.g. [`crate::Query::matches`].
+pub fn optimize<T: Amount>(
+    numbers: &[T],
+    target: T,
+    tuples: impl IntoIterator<Item = Vec<usize>>,
+    objective: Objective,
+    goal: Goal,
+) -> Optimum<T> {
+    let mut optimum = Optimum {
+        best: None,
+        considered: 0,
+        overflowed: 0,
+    };
+
+    for indices in tuples {
+        optimum.considered += 1;
+        let entries: Vec<T> = indices.iter().map(|index| numbers[*index]).collect();
+        let score = match objective.score(&entries) {
+            Some(score) => score,
+            None => {
+                optimum.overflowed += 1;
+                continue;
+            }
+        };
+
+        let better = match &optimum.best {
+            None => true,
+            Some((_, best)) => match goal {
+                Goal::Maximize => score > *best,
+                Goal::Minimize => score < *best,
+            },
+        };
+        if better {
+            let mut indices = indices;
+            indices.sort_by_key(|index| (numbers[*index], *index));
+            optimum.best = Some((SearchResult::new(numbers, target, indices), score));
+        }
+    }
+
+    optimum
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objective::{optimize, Goal, Objective};
+    use crate::search::Query;
+
+    #[test]
+    fn should_parse_objective_names() {
+        for objective in Objective::ALL.iter() {
+            assert_eq!(objective.name().parse(), Ok(*objective))
+        }
+        assert_eq!(
+            "quotient".parse::<Objective>().unwrap_err().to_string(),
+            "unknown objective \"quotient\", expected one of product, sum-of-squares, spread, largest"
+        )
+    }
+
+    #[test]
+    fn should_score_entries() {
+        let entries: Vec<i64> = vec![-3, 4, 5];
+
+        assert_eq!(Objective::Product.score(&entries), Some(-60));
+        assert_eq!(Objective::SumOfSquares.score(&entries), Some(50));
+        assert_eq!(Objective::Spread.score(&entries), Some(8));
+        assert_eq!(Objective::Largest.score(&entries), Some(5))
+    }
+
+    #[test]
+    fn should_pick_extreme_products() {
+        let numbers = vec![1, 2, 3, 4, 5, 6];
+        let query = Query::new(2, 7);
+        let largest = optimize(
+            &numbers,
+            7,
+            query.matches(&numbers),
+            Objective::Product,
+            Goal::Maximize,
+        );
+        let smallest = optimize(
+            &numbers,
+            7,
+            query.matches(&numbers),
+            Objective::Product,
+            Goal::Minimize,
+        );
+
+        assert_eq!(largest.considered, 3);
+        let (best, product) = largest.best.unwrap();
+        assert_eq!((best.entries, product), (vec![3, 4], 12));
+        assert_eq!(smallest.best.unwrap().0.entries, vec![1, 6])
+    }
+
+    #[test]
+    fn should_leave_out_overflowing_scores() {
+        let numbers: Vec<u8> = vec![8, 16, 1, 9];
+        let optimum = optimize(
+            &numbers,
+            17,
+            Query::new(2, 17).matches(&numbers),
+            Objective::SumOfSquares,
+            Goal::Maximize,
+        );
+
+        assert_eq!(optimum.considered, 2);
+        assert_eq!(optimum.overflowed, 1);
+        assert_eq!(optimum.best.unwrap().1, 64 + 81)
+    }
+
+    #[test]
+    fn should_consider_nothing_without_matches() {
+        let numbers = vec![1, 2];
+        let optimum = optimize(
+            &numbers,
+            10,
+            Query::new(2, 10).matches(&numbers),
+            Objective::Spread,
+            Goal::Minimize,
+        );
+
+        assert_eq!((optimum.best, optimum.considered), (None, 0))
+    }
+}
@@ -0,0 +1,103 @@
+use crate::amount::Amount;
+use crate::search::{Matches, Query, SearchResult, Sorted};
+use rayon::prelude::*;
+use std::iter;
+use std::sync::Arc;
+
+/// Walks of the matches starting with `first`, one per first position that may still fit.
+fn seeded<T: Amount>(
+    sorted: &Arc<Sorted<T>>,
+    query: &Query<T>,
+) -> impl IndexedParallelIterator<Item = Option<Matches<T>>> {
+    let sorted = sorted.clone();
+    let query = *query;
+
+    (0..sorted.values.len()).into_par_iter().map(move |first| {
+        if sorted.overshoots(
+            iter::empty(),
+            first,
+            query.k,
+            query.with_replacement,
+            query.target,
+        ) {
+            None
+        } else {
+            Some(Matches::new(sorted.clone(), query, vec![first]))
+        }
+    })
+}
+
+pub(crate) fn find<T: Amount>(numbers: &[T], query: &Query<T>) -> Option<SearchResult<T>> {
+    let query = query.with_strategy(Default::default());
+    if query.k < 2 {
+        return query.find(numbers);
+    }
+
+    let sorted = Arc::new(Sorted::new(numbers));
+    let mut indices = seeded(&sorted, &query).find_map_first(|matches| matches?.next())?;
+    indices.sort_by_key(|index| (numbers[*index], *index));
+
+    Some(SearchResult::new(numbers, query.target, indices))
+}
+
+pub(crate) fn count<T: Amount>(numbers: &[T], query: &Query<T>) -> usize {
+    if query.k < 2 {
+        return query.matches(numbers).count();
+    }
+
+    let sorted = Arc::new(Sorted::new(numbers));
+    seeded(&sorted, query)
+        .map(|matches| matches.map_or(0, Iterator::count))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::Query;
+
+    #[test]
+    fn should_find_aoc_triple_in_parallel() {
+        let numbers = vec![1721, 979, 366, 299, 675, 1456];
+        let result = Query::new(3, 2020).par_find(&numbers).unwrap();
+
+        assert_eq!(result.entries, vec![366, 675, 979])
+    }
+
+    #[test]
+    fn should_agree_with_sequential_search() {
+        let numbers: Vec<i64> = (0..80).map(|i| (i * 53 + 17) % 211 - 60).collect();
+
+        for k in 1..=4 {
+            for target in -100..300 {
+                for with_replacement in [false, true].iter() {
+                    let query = Query::new(k, target).with_replacement(*with_replacement);
+
+                    assert_eq!(
+                        query.par_find(&numbers),
+                        query.find(&numbers),
+                        "{:?}",
+                        query
+                    );
+                }
+            }
+        }
+
+        for numbers in [[i64::MIN, -1, 100], [100, -1, i64::MIN]] {
+            let query = Query::new(3, i64::MIN + 99);
+
+            assert_eq!(query.par_find(&numbers), query.find(&numbers));
+            assert_eq!(query.par_count(&numbers), 1)
+        }
+    }
+
+    #[test]
+    fn should_count_like_sequential_enumeration() {
+        let numbers: Vec<u64> = (0..60).map(|i| (i * 37 + 11) % 101).collect();
+
+        for k in 1..=3 {
+            let query = Query::new(k, 120);
+
+            assert_eq!(query.par_count(&numbers), query.matches(&numbers).count())
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::amount::Amount;
+use crate::records::{parse_delimited, Format, Record};
+use std::num::ParseIntError;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Why a token or row of the expense report could not be read.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum EntryError {
+    #[error(transparent)]
+    Amount(#[from] ParseIntError),
+    #[error("expected {expected} fields, found {found}")]
+    FieldCount { expected: usize, found: usize },
+    #[error("unterminated quote")]
+    UnterminatedQuote,
+}
+
+/// A token of the expense report that is not a number, positioned by 1-based line and column.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("line {line}, column {column}: {token:?} is not a valid entry ({source})")]
+pub struct InvalidEntry {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    #[source]
+    pub source: EntryError,
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("expense report contains {} invalid entries", .0.len())]
+pub struct ParseError(pub Vec<InvalidEntry>);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Strictness {
+    /// Any invalid token fails the whole report.
+    Strict,
+    /// Invalid tokens are skipped and listed in [`ExpenseReport::skipped`].
+    Lenient,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExpenseReport<T> {
+    pub numbers: Vec<T>,
+    /// Describes every entry of `numbers` for CSV and TSV input, empty for plain numbers.
+    pub records: Vec<Record>,
+    pub skipped: Vec<InvalidEntry>,
+}
+
+impl<T: Amount> ExpenseReport<T> {
+    /// Reads plain whitespace separated numbers, or records if the report is a CSV or TSV file.
+    pub fn parse(contents: &str, strictness: Strictness) -> Result<Self, ParseError> {
+        let mut report = ExpenseReport {
+            numbers: Vec::new(),
+            records: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        match Format::detect(contents) {
+            Format::Plain => {
+                for (line_index, line) in contents.lines().enumerate() {
+                    for entry in parse_line(line_index + 1, line) {
+                        match entry {
+                            Ok(number) => report.numbers.push(number),
+                            Err(invalid) => report.skipped.push(invalid),
+                        }
+                    }
+                }
+            }
+            Format::Delimited(delimiter) => parse_delimited(contents, delimiter, &mut report),
+        }
+
+        match strictness {
+            Strictness::Strict if !report.skipped.is_empty() => Err(ParseError(report.skipped)),
+            _ => Ok(report),
+        }
+    }
+}
+
+impl<T: Amount> FromStr for ExpenseReport<T> {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        ExpenseReport::parse(contents, Strictness::Strict)
+    }
+}
+
+/// Parses the whitespace separated entries of the line with 1-based number `line_number`.
+pub fn parse_line<T: Amount>(
+    line_number: usize,
+    line: &str,
+) -> impl Iterator<Item = Result<T, InvalidEntry>> + '_ {
+    tokens(line).map(move |(offset, token)| {
+        token.parse::<T>().map_err(|source| InvalidEntry {
+            line: line_number,
+            column: line[..offset].chars().count() + 1,
+            token: token.into(),
+            source: source.into(),
+        })
+    })
+}
+
+/// Splits a line at whitespace, keeping the byte offset of every token.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace()
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{ExpenseReport, Strictness};
+    use std::str::FromStr;
+
+    #[test]
+    fn should_parse_numbers() {
+        let report = ExpenseReport::<usize>::from_str("1721\n979\n\n366 299\n").unwrap();
+
+        assert_eq!(report.numbers, vec![1721, 979, 366, 299]);
+        assert!(report.skipped.is_empty())
+    }
+
+    #[test]
+    fn should_keep_zero_and_large_entries() {
+        let report = ExpenseReport::<usize>::from_str("0\n2020\n5000").unwrap();
+
+        assert_eq!(report.numbers, vec![0, 2020, 5000])
+    }
+
+    #[test]
+    fn should_parse_signed_and_wide_amounts() {
+        let signed = ExpenseReport::<i64>::from_str("-480\n2500").unwrap();
+        let wide =
+            ExpenseReport::<u128>::from_str("340282366920938463463374607431768211455").unwrap();
+
+        assert_eq!(signed.numbers, vec![-480, 2500]);
+        assert_eq!(wide.numbers, vec![u128::MAX])
+    }
+
+    #[test]
+    fn should_report_every_invalid_token() {
+        let errors = ExpenseReport::<usize>::from_str("1721\n97x9\n366  ab\n")
+            .unwrap_err()
+            .0;
+        let positions: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.token.as_str()))
+            .collect();
+
+        assert_eq!(positions, vec![(2, 1, "97x9"), (3, 6, "ab")])
+    }
+
+    #[test]
+    fn should_count_columns_in_characters() {
+        let errors = ExpenseReport::<usize>::from_str("äö 12 -3").unwrap_err().0;
+
+        assert_eq!(errors[0].column, 1);
+        assert_eq!(errors[1].column, 7)
+    }
+
+    #[test]
+    fn should_skip_invalid_tokens_leniently() {
+        let report = ExpenseReport::<usize>::parse("1721\nabc\n979", Strictness::Lenient).unwrap();
+
+        assert_eq!(report.numbers, vec![1721, 979]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(
+            report.skipped[0].to_string(),
+            "line 2, column 1: \"abc\" is not a valid entry (invalid digit found in string)"
+        )
+    }
+}
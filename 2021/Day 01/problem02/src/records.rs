@@ -0,0 +1,305 @@
+use crate::amount::Amount;
+use crate::parse::{EntryError, ExpenseReport, InvalidEntry};
+use std::fmt::{self, Display, Formatter};
+
+/// Describes an expense of a delimited report, its amount is kept in [`ExpenseReport::numbers`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Record {
+    pub id: String,
+    pub label: String,
+    pub category: String,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?}", self.id, self.label)?;
+        if !self.category.is_empty() {
+            write!(f, " [{}]", self.category)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// Bare whitespace separated numbers.
+    Plain,
+    /// One record per line, fields separated by the given character.
+    Delimited(char),
+}
+
+impl Format {
+    /// Tabs or commas on the first non-empty line make a delimited report.
+    pub fn detect(contents: &str) -> Self {
+        let first = contents.lines().find(|line| !line.trim().is_empty());
+
+        match first {
+            Some(line) if line.contains('\t') => Format::Delimited('\t'),
+            Some(line) if line.contains(',') => Format::Delimited(','),
+            _ => Format::Plain,
+        }
+    }
+}
+
+const COLUMNS: [&str; 4] = ["id", "label", "category", "amount"];
+
+/// Position of every known column within a row.
+struct Layout {
+    width: usize,
+    positions: [Option<usize>; 4],
+}
+
+impl Layout {
+    /// Reads the layout from a header naming an `amount` column.
+    fn from_header(fields: &[Field]) -> Option<Self> {
+        let find = |name: &str| {
+            fields
+                .iter()
+                .position(|field| field.text.eq_ignore_ascii_case(name))
+        };
+        let positions = [find("id"), find("label"), find("category"), find("amount")];
+
+        positions[3].map(|_| Layout {
+            width: fields.len(),
+            positions,
+        })
+    }
+
+    /// Without a header, records are `id, label, category, amount`.
+    fn positional() -> Self {
+        Layout {
+            width: COLUMNS.len(),
+            positions: [Some(0), Some(1), Some(2), Some(3)],
+        }
+    }
+
+    fn text<'a>(&self, fields: &'a [Field], column: usize) -> &'a str {
+        self.positions[column].map_or("", |position| fields[position].text.as_str())
+    }
+}
+
+/// A field of a delimited line, with the byte offset it starts at.
+struct Field {
+    offset: usize,
+    text: String,
+}
+
+pub(crate) fn parse_delimited<T: Amount>(
+    contents: &str,
+    delimiter: char,
+    report: &mut ExpenseReport<T>,
+) {
+    let mut layout: Option<Layout> = None;
+
+    for (line_index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |offset: usize, token: &str, source: EntryError| InvalidEntry {
+            line: line_index + 1,
+            column: line[..offset].chars().count() + 1,
+            token: token.into(),
+            source,
+        };
+
+        let fields = match split(line, delimiter) {
+            Ok(fields) => fields,
+            Err(offset) => {
+                let source = EntryError::UnterminatedQuote;
+                report
+                    .skipped
+                    .push(invalid(offset, &line[offset..], source));
+                continue;
+            }
+        };
+
+        if layout.is_none() {
+            let header = Layout::from_header(&fields);
+            let is_header = header.is_some();
+            layout = Some(header.unwrap_or_else(Layout::positional));
+            if is_header {
+                continue;
+            }
+        }
+        let layout = layout
+            .as_ref()
+            .expect("layout is known after the first row");
+
+        if fields.len() != layout.width {
+            let source = EntryError::FieldCount {
+                expected: layout.width,
+                found: fields.len(),
+            };
+            report.skipped.push(invalid(0, line, source));
+            continue;
+        }
+
+        let amount = &fields[layout.positions[3].expect("layout has an amount")];
+        match amount.text.parse::<T>() {
+            Ok(number) => {
+                report.numbers.push(number);
+                report.records.push(Record {
+                    id: layout.text(&fields, 0).into(),
+                    label: layout.text(&fields, 1).into(),
+                    category: layout.text(&fields, 2).into(),
+                });
+            }
+            Err(source) => {
+                let source = EntryError::Amount(source);
+                report
+                    .skipped
+                    .push(invalid(amount.offset, &amount.text, source));
+            }
+        }
+    }
+}
+
+/// Splits `line` at `delimiter`, honouring double quoted fields with `""` as escaped quote.
+///
+/// Fails with the offset of an unterminated opening quote.
+fn split(line: &str, delimiter: char) -> Result<Vec<Field>, usize> {
+    let mut fields = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    loop {
+        while let Some((_, ' ')) = chars.peek() {
+            chars.next();
+        }
+        let offset = chars.peek().map_or(line.len(), |(offset, _)| *offset);
+        let mut text = String::new();
+
+        if let Some((_, '"')) = chars.peek() {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) if matches!(chars.peek(), Some((_, '"'))) => {
+                        chars.next();
+                        text.push('"');
+                    }
+                    Some((_, '"')) => break,
+                    Some((_, c)) => text.push(c),
+                    None => return Err(offset),
+                }
+            }
+            while let Some((_, c)) = chars.peek() {
+                if *c == delimiter {
+                    break;
+                }
+                chars.next();
+            }
+        } else {
+            while let Some((_, c)) = chars.peek() {
+                if *c == delimiter {
+                    break;
+                }
+                text.push(*c);
+                chars.next();
+            }
+            text.truncate(text.trim_end().len());
+        }
+
+        fields.push(Field { offset, text });
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{EntryError, ExpenseReport, Strictness};
+    use crate::records::{Format, Record};
+    use std::str::FromStr;
+
+    #[test]
+    fn should_detect_formats() {
+        assert_eq!(Format::detect("\n1721\n979"), Format::Plain);
+        assert_eq!(
+            Format::detect("1,Hotel,travel,1721"),
+            Format::Delimited(',')
+        );
+        assert_eq!(
+            Format::detect("1\tHotel\ttravel\t1721"),
+            Format::Delimited('\t')
+        )
+    }
+
+    #[test]
+    fn should_parse_csv_with_header() {
+        let input = "amount,id,label\n1721,a1,Hotel\n979, a2 ,\"Dinner, team\"\n";
+        let report = ExpenseReport::<i64>::from_str(input).unwrap();
+
+        assert_eq!(report.numbers, vec![1721, 979]);
+        assert_eq!(
+            report.records[1],
+            Record {
+                id: "a2".into(),
+                label: "Dinner, team".into(),
+                category: "".into(),
+            }
+        )
+    }
+
+    #[test]
+    fn should_parse_tsv_without_header() {
+        let input = "7\tTrain \"ICE\"\ttravel\t-299\n";
+        let report = ExpenseReport::<i64>::from_str(input).unwrap();
+
+        assert_eq!(report.numbers, vec![-299]);
+        assert_eq!(report.records[0].label, "Train \"ICE\"");
+        assert_eq!(
+            report.records[0].to_string(),
+            "7 \"Train \\\"ICE\\\"\" [travel]"
+        )
+    }
+
+    #[test]
+    fn should_unescape_quotes() {
+        let input = "1,\"say \"\"hi\"\"\",misc,5\n";
+        let report = ExpenseReport::<i64>::from_str(input).unwrap();
+
+        assert_eq!(report.records[0].label, "say \"hi\"")
+    }
+
+    #[test]
+    fn should_position_invalid_amounts() {
+        let input = "id,label,category,amount\n1,Hotel,travel,17x1\n";
+        let errors = ExpenseReport::<i64>::from_str(input).unwrap_err().0;
+
+        assert_eq!((errors[0].line, errors[0].column), (2, 16));
+        assert_eq!(errors[0].token, "17x1")
+    }
+
+    #[test]
+    fn should_report_malformed_rows() {
+        let input = "1,Hotel,travel,1721\n2,Dinner,979\n3,\"Taxi,travel,20\n";
+        let report = ExpenseReport::<i64>::parse(input, Strictness::Lenient).unwrap();
+        let problems: Vec<(usize, &EntryError)> = report
+            .skipped
+            .iter()
+            .map(|invalid| (invalid.line, &invalid.source))
+            .collect();
+
+        assert_eq!(report.numbers, vec![1721]);
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    2,
+                    &EntryError::FieldCount {
+                        expected: 4,
+                        found: 3
+                    }
+                ),
+                (3, &EntryError::UnterminatedQuote),
+            ]
+        )
+    }
+
+    #[test]
+    fn should_keep_plain_reports_without_records() {
+        let report = ExpenseReport::<i64>::from_str("1721\n979").unwrap();
+
+        assert!(report.records.is_empty())
+    }
+}
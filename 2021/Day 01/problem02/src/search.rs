@@ -0,0 +1,569 @@
+use crate::amount::{checked_product, compare_total, remainder, total, Amount};
+use crate::closest::{self, Closest, Fit};
+use crate::parallel;
+use crate::strategy::{self, Strategy};
+use std::cmp::Ordering;
+use std::iter;
+use std::ops::Range;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SearchResult<T> {
+    pub target: T,
+    pub indices: Vec<usize>,
+    pub entries: Vec<T>,
+}
+
+impl<T: Amount> SearchResult<T> {
+    pub fn new(numbers: &[T], target: T, indices: Vec<usize>) -> Self {
+        let entries = indices.iter().map(|index| numbers[*index]).collect();
+
+        SearchResult {
+            target,
+            indices,
+            entries,
+        }
+    }
+
+    /// Adds up the entries, `None` if the sum does not fit into `T`.
+    pub fn sum(&self) -> Option<T> {
+        total(self.entries.iter().copied()).ok()
+    }
+
+    /// Multiplies the entries, `None` if the product does not fit into `T`.
+    pub fn product(&self) -> Option<T> {
+        checked_product(self.entries.iter().copied())
+    }
+}
+
+/// Describes which tuples of an expense report are searched for.
+///
+/// By default every entry is used at most once, so equal values only pair up when they occur more
+/// than once in the input. `with_replacement` allows an entry to be picked repeatedly.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Query<T> {
+    pub k: usize,
+    pub target: T,
+    pub with_replacement: bool,
+    pub strategy: Strategy,
+}
+
+impl<T: Amount> Query<T> {
+    pub fn new(k: usize, target: T) -> Self {
+        Query {
+            k,
+            target,
+            with_replacement: false,
+            strategy: Strategy::default(),
+        }
+    }
+
+    pub fn with_replacement(mut self, with_replacement: bool) -> Self {
+        self.with_replacement = with_replacement;
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Returns a single match, found with the query's strategy.
+    ///
+    /// Its entries are ordered by ascending value.
+    pub fn find(&self, numbers: &[T]) -> Option<SearchResult<T>> {
+        let mut indices = match self.strategy {
+            _ if self.k < 2 => self.matches(numbers).next(),
+            Strategy::BinarySearch => self.matches(numbers).next(),
+            Strategy::HashSet => strategy::hash_set(numbers, self),
+            Strategy::TwoPointer => strategy::two_pointer(numbers, self),
+        }?;
+        indices.sort_by_key(|index| (numbers[*index], *index));
+
+        Some(SearchResult::new(numbers, self.target, indices))
+    }
+
+    /// Returns the tuple whose sum is nearest to the target, an exact match having distance zero.
+    ///
+    /// Looks at every tuple, so this is considerably slower than [`Query::find`] for `k > 2`.
+    pub fn closest(&self, numbers: &[T]) -> Option<Closest<T>> {
+        closest::closest(numbers, self, Fit::Nearest)
+    }
+
+    /// Lazily enumerates every multiset of indices into `numbers` whose entries add up to the
+    /// target.
+    ///
+    /// Each multiset is yielded once, ordered by ascending entry value.
+    pub fn matches(&self, numbers: &[T]) -> Matches<T> {
+        Matches::new(
+            Arc::new(Sorted::new(numbers)),
+            *self,
+            Vec::with_capacity(self.k),
+        )
+    }
+
+    /// Lazily enumerates every multiset of indices into `numbers` whose entries add up to a sum
+    /// within `lower..=upper`, ignoring the target.
+    ///
+    /// Each multiset is yielded once, ordered by ascending entry value.
+    pub fn between(&self, numbers: &[T], lower: T, upper: T) -> Matches<T> {
+        self.matches(numbers).between(lower, upper)
+    }
+
+    /// Returns the tuple with the largest sum not exceeding the target, treated as a budget.
+    ///
+    /// Its distance is the part of the budget left over.
+    pub fn at_most(&self, numbers: &[T]) -> Option<Closest<T>> {
+        closest::closest(numbers, self, Fit::AtMost)
+    }
+
+    /// Same as [`Query::find`] with the binary search strategy, spreading the candidates for the
+    /// first entry over all threads of the rayon pool.
+    ///
+    /// The result does not depend on the number of threads.
+    pub fn par_find(&self, numbers: &[T]) -> Option<SearchResult<T>> {
+        parallel::find(numbers, self)
+    }
+
+    /// Counts what [`Query::matches`] enumerates, on all threads of the rayon pool.
+    pub fn par_count(&self, numbers: &[T]) -> usize {
+        parallel::count(numbers, self)
+    }
+}
+
+/// Finds `k` distinct entries of `numbers` adding up to `target`.
+pub fn find_k_sum<T: Amount>(numbers: &[T], k: usize, target: T) -> Option<SearchResult<T>> {
+    Query::new(k, target).find(numbers)
+}
+
+pub fn find_matching_pair<T: Amount>(numbers: &[T], target: T) -> Option<SearchResult<T>> {
+    find_k_sum(numbers, 2, target)
+}
+
+pub fn find_matching_triple<T: Amount>(numbers: &[T], target: T) -> Option<SearchResult<T>> {
+    find_k_sum(numbers, 3, target)
+}
+
+/// Lazily enumerates every set of `k` distinct indices into `numbers` whose entries add up to
+/// `target`.
+pub fn k_sum_matches<T: Amount>(numbers: &[T], k: usize, target: T) -> Matches<T> {
+    Query::new(k, target).matches(numbers)
+}
+
+/// Entries in ascending order together with their original indices.
+#[derive(Debug)]
+pub(crate) struct Sorted<T> {
+    pub(crate) order: Vec<usize>,
+    pub(crate) values: Vec<T>,
+}
+
+impl<T: Amount> Sorted<T> {
+    pub(crate) fn new(numbers: &[T]) -> Self {
+        let mut order: Vec<usize> = (0..numbers.len()).collect();
+        order.sort_by_key(|index| numbers[*index]);
+        let values = order.iter().map(|index| numbers[*index]).collect();
+
+        Sorted { order, values }
+    }
+
+    /// Whether `prefix` and the `count` smallest entries picked at or after `position` add up to
+    /// more than `target`, or there are not enough entries left to pick from.
+    ///
+    /// As the entries are sorted, this holds for every later position as well. The entries are
+    /// compared with [`compare_total`], so partial sums may leave the range of `T`.
+    pub(crate) fn overshoots(
+        &self,
+        prefix: impl Iterator<Item = T> + Clone,
+        position: usize,
+        count: usize,
+        with_replacement: bool,
+        target: T,
+    ) -> bool {
+        let ordering = if with_replacement {
+            match self.values.get(position) {
+                Some(value) => compare_total(prefix.chain(iter::repeat_n(*value, count)), target),
+                None => return true,
+            }
+        } else {
+            match self.values.get(position..position + count) {
+                Some(values) => compare_total(prefix.chain(values.iter().copied()), target),
+                None => return true,
+            }
+        };
+
+        ordering == Ordering::Greater
+    }
+}
+
+/// Depth first walk over the sorted entries.
+///
+/// `prefix` holds non-decreasing positions (into the sorted values) of the first `k - 1` entries,
+/// `leaves` the positions that complete the current prefix to a sum within `lower..=upper`, which
+/// both are the target unless set with [`Matches::between`]. The first `floor` positions of the
+/// prefix are fixed and never backtracked.
+#[derive(Debug)]
+pub struct Matches<T> {
+    sorted: Arc<Sorted<T>>,
+    query: Query<T>,
+    lower: T,
+    upper: T,
+    prefix: Vec<usize>,
+    floor: usize,
+    leaves: Range<usize>,
+    started: bool,
+}
+
+impl<T: Amount> Iterator for Matches<T> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.query.k == 0 {
+            let first = !self.started;
+            self.started = true;
+            return if first && self.lower <= T::ZERO && T::ZERO <= self.upper {
+                Some(Vec::new())
+            } else {
+                None
+            };
+        }
+
+        loop {
+            if let Some(leaf) = self.leaves.next() {
+                return Some(self.indices_with(leaf));
+            }
+
+            let moved = if self.started {
+                self.bump()
+            } else {
+                self.started = true;
+                true
+            };
+
+            if !moved || !self.fill() {
+                return None;
+            }
+
+            self.leaves = self.leaf_range();
+        }
+    }
+}
+
+impl<T: Amount> Matches<T> {
+    /// Walks only the matches starting with the positions in `prefix`.
+    pub(crate) fn new(sorted: Arc<Sorted<T>>, query: Query<T>, prefix: Vec<usize>) -> Self {
+        Matches {
+            sorted,
+            query,
+            lower: query.target,
+            upper: query.target,
+            floor: prefix.len(),
+            prefix,
+            leaves: 0..0,
+            started: false,
+        }
+    }
+
+    /// Matches sums within `lower..=upper` instead of the target.
+    pub(crate) fn between(mut self, lower: T, upper: T) -> Self {
+        self.lower = lower;
+        self.upper = upper;
+        self
+    }
+
+    fn prefix_values(&self) -> impl Iterator<Item = T> + Clone + '_ {
+        self.prefix
+            .iter()
+            .map(move |position| self.sorted.values[*position])
+    }
+
+    fn fits(&self, position: usize) -> bool {
+        !self.sorted.overshoots(
+            self.prefix_values(),
+            position,
+            self.query.k - self.prefix.len(),
+            self.query.with_replacement,
+            self.upper,
+        )
+    }
+
+    fn next_start(&self) -> usize {
+        match self.prefix.last() {
+            Some(position) if self.query.with_replacement => *position,
+            Some(position) => position + 1,
+            None => 0,
+        }
+    }
+
+    /// Moves the prefix to its next sibling, backtracking where a level is exhausted.
+    fn bump(&mut self) -> bool {
+        while self.prefix.len() > self.floor {
+            let position = self.prefix.pop().expect("prefix above floor");
+            if self.fits(position + 1) {
+                self.prefix.push(position + 1);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Extends the prefix to `k - 1` positions.
+    fn fill(&mut self) -> bool {
+        while self.prefix.len() < self.query.k - 1 {
+            let start = self.next_start();
+            if self.fits(start) {
+                self.prefix.push(start);
+            } else if !self.bump() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn leaf_range(&self) -> Range<usize> {
+        let start = self.next_start();
+        let rest = &self.sorted.values[start.min(self.sorted.values.len())..];
+        // The leaf completing the prefix to a bound, if it fits into `T`. A prefix exceeding the
+        // bound by more than `T` can make up for puts every leaf above it, and vice versa.
+        let lower = remainder(self.lower, self.prefix_values());
+        let upper = remainder(self.upper, self.prefix_values());
+
+        let first = start
+            + rest.partition_point(|value| match lower {
+                Ok(leaf) => *value < leaf,
+                Err(ordering) => ordering == Ordering::Less,
+            });
+        let end = start
+            + rest.partition_point(|value| match upper {
+                Ok(leaf) => *value <= leaf,
+                Err(ordering) => ordering == Ordering::Less,
+            });
+
+        first..end
+    }
+
+    fn indices_with(&self, leaf: usize) -> Vec<usize> {
+        self.prefix
+            .iter()
+            .chain(iter::once(&leaf))
+            .map(|position| self.sorted.order[*position])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{
+        find_k_sum, find_matching_pair, find_matching_triple, k_sum_matches, Query,
+    };
+
+    #[test]
+    fn should_find_aoc_pair() {
+        let numbers = given_aoc_numbers();
+        let result = find_matching_pair(&numbers, 2020).unwrap();
+
+        assert_eq!(result.entries, vec![299, 1721]);
+        assert_eq!(result.indices, vec![3, 0]);
+        assert_eq!(result.product(), Some(514579))
+    }
+
+    #[test]
+    fn should_find_aoc_triple() {
+        let numbers = given_aoc_numbers();
+        let result = find_matching_triple(&numbers, 2020).unwrap();
+
+        assert_eq!(result.entries, vec![366, 675, 979]);
+        assert_eq!(result.product(), Some(241861950))
+    }
+
+    #[test]
+    fn should_find_four_entries() {
+        let numbers = given_aoc_numbers();
+        let result = find_k_sum(&numbers, 4, 366 + 675 + 979 + 1456).unwrap();
+
+        assert_eq!(result.entries, vec![366, 675, 979, 1456])
+    }
+
+    #[test]
+    fn should_find_nothing_for_unreachable_target() {
+        let numbers = given_aoc_numbers();
+
+        assert_eq!(find_k_sum(&numbers, 2, 1), None)
+    }
+
+    #[test]
+    fn should_not_use_an_entry_twice() {
+        let numbers = vec![1010, 1721];
+
+        assert_eq!(find_matching_pair(&numbers, 2020), None)
+    }
+
+    #[test]
+    fn should_enumerate_all_pairs() {
+        let numbers = vec![1, 2, 3, 4, 5];
+        let matches: Vec<Vec<usize>> = k_sum_matches(&numbers, 2, 6).collect();
+
+        assert_eq!(matches, vec![vec![0, 4], vec![1, 3]])
+    }
+
+    #[test]
+    fn should_enumerate_all_triples() {
+        let numbers = vec![6, 1, 5, 2, 4, 3];
+        let matches: Vec<Vec<usize>> = k_sum_matches(&numbers, 3, 10).collect();
+
+        assert_eq!(matches, vec![vec![1, 5, 0], vec![1, 4, 2], vec![3, 5, 2]])
+    }
+
+    #[test]
+    fn should_count_every_index_tuple() {
+        let numbers = vec![1, 2, 2, 2, 3];
+
+        assert_eq!(k_sum_matches(&numbers, 2, 4).count(), 4)
+    }
+
+    #[test]
+    fn should_pair_duplicate_values() {
+        let numbers = vec![1010, 1721, 1010];
+        let result = find_matching_pair(&numbers, 2020).unwrap();
+
+        assert_eq!(result.indices, vec![0, 2])
+    }
+
+    #[test]
+    fn should_reuse_an_entry_with_replacement() {
+        let numbers = vec![1721, 1010];
+        let result = Query::new(2, 2020)
+            .with_replacement(true)
+            .find(&numbers)
+            .unwrap();
+
+        assert_eq!(result.indices, vec![1, 1]);
+        assert_eq!(result.entries, vec![1010, 1010])
+    }
+
+    #[test]
+    fn should_enumerate_multisets_with_replacement() {
+        let numbers = vec![1010, 1010];
+        let matches: Vec<Vec<usize>> = Query::new(2, 2020)
+            .with_replacement(true)
+            .matches(&numbers)
+            .collect();
+
+        assert_eq!(matches, vec![vec![0, 0], vec![0, 1], vec![1, 1]])
+    }
+
+    #[test]
+    fn should_match_refunds() {
+        let numbers: Vec<i64> = vec![2500, -480, 1000, -20, 3000, 1040];
+        let matches: Vec<Vec<usize>> = k_sum_matches(&numbers, 2, 2020).collect();
+
+        assert_eq!(matches, vec![vec![1, 0]]);
+        assert_eq!(
+            find_matching_triple(&numbers, 2020).unwrap().entries,
+            vec![-20, 1000, 1040]
+        )
+    }
+
+    #[test]
+    fn should_match_a_negative_target() {
+        let numbers: Vec<i64> = vec![-7, 3, -2, 5];
+        let result = find_matching_pair(&numbers, -9).unwrap();
+
+        assert_eq!(result.entries, vec![-7, -2]);
+        assert_eq!(result.product(), Some(14))
+    }
+
+    #[test]
+    fn should_match_wide_amounts() {
+        let large: u128 = u128::MAX / 3;
+        let numbers: Vec<u128> = vec![large, 5, large + 7, 2];
+        let result = find_matching_pair(&numbers, 2 * large + 7).unwrap();
+
+        assert_eq!(result.indices, vec![0, 2]);
+        assert_eq!(result.product(), None)
+    }
+
+    #[test]
+    fn should_not_match_overflowing_sums() {
+        let numbers: Vec<u8> = vec![200, 100, 56];
+
+        assert_eq!(find_matching_pair(&numbers, 44), None);
+        assert_eq!(
+            find_matching_pair(&numbers, 156).unwrap().entries,
+            vec![56, 100]
+        )
+    }
+
+    #[test]
+    fn should_enumerate_pairs_within_range() {
+        let numbers = vec![1, 2, 3, 4, 5];
+        let matches: Vec<Vec<usize>> = Query::new(2, 0).between(&numbers, 6, 7).collect();
+
+        assert_eq!(
+            matches,
+            vec![vec![0, 4], vec![1, 3], vec![1, 4], vec![2, 3]]
+        )
+    }
+
+    #[test]
+    fn should_enumerate_triples_within_range() {
+        let numbers: Vec<i64> = vec![-5, 10, 3, 8, 1];
+        let sums: Vec<i64> = Query::new(3, 0)
+            .between(&numbers, 4, 12)
+            .map(|indices| indices.iter().map(|index| numbers[*index]).sum())
+            .collect();
+
+        assert_eq!(sums, vec![4, 6, 6, 8, 12])
+    }
+
+    #[test]
+    fn should_count_range_as_sum_of_exact_targets() {
+        let numbers: Vec<i64> = (0..40).map(|i| (i * 37 + 11) % 97 - 30).collect();
+
+        for k in 1..=3 {
+            let query = Query::new(k, 0).with_replacement(k == 3);
+            let exact: usize = (-20..=45)
+                .map(|target| Query { target, ..query }.matches(&numbers).count())
+                .sum();
+
+            assert_eq!(query.between(&numbers, -20, 45).count(), exact, "k = {}", k)
+        }
+    }
+
+    #[test]
+    fn should_find_nothing_in_an_empty_range() {
+        let numbers = given_aoc_numbers();
+
+        assert_eq!(Query::new(2, 0).between(&numbers, 10, 1).next(), None)
+    }
+
+    #[test]
+    fn should_keep_overflowing_sums_out_of_range() {
+        let numbers: Vec<u8> = vec![200, 100, 56];
+        let matches: Vec<Vec<usize>> = Query::new(2, 0).between(&numbers, 150, 255).collect();
+
+        assert_eq!(matches, vec![vec![2, 1]])
+    }
+
+    #[test]
+    fn should_spend_budget_on_largest_pair() {
+        let numbers = given_aoc_numbers();
+        let best = Query::new(2, 2000).at_most(&numbers).unwrap();
+
+        assert_eq!(best.result.entries, vec![366, 1456]);
+        assert_eq!(best.sum, 1822);
+        assert_eq!(best.distance, 178)
+    }
+
+    #[test]
+    fn should_have_nothing_within_a_too_small_budget() {
+        let numbers = given_aoc_numbers();
+
+        assert_eq!(Query::new(2, 600).at_most(&numbers), None)
+    }
+
+    fn given_aoc_numbers() -> Vec<usize> {
+        vec![1721, 979, 366, 299, 675, 1456]
+    }
+}
@@ -0,0 +1,347 @@
+use crate::amount::{compare_total, remainder, Amount};
+use crate::search::{Query, Sorted};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::iter;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Algorithm completing the last two entries of a k-sum search.
+///
+/// All strategies find a valid match if there is one, but with several matches in the input they
+/// may pick different ones.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Strategy {
+    /// Sorts the entries and looks up every partner with a binary search.
+    #[default]
+    BinarySearch,
+    /// Works on the unsorted entries, remembering seen values in a hash map.
+    HashSet,
+    /// Sorts the entries and sweeps inwards from both ends.
+    TwoPointer,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [
+        Strategy::BinarySearch,
+        Strategy::HashSet,
+        Strategy::TwoPointer,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::BinarySearch => "binary-search",
+            Strategy::HashSet => "hash-set",
+            Strategy::TwoPointer => "two-pointer",
+        }
+    }
+
+    /// Names of all strategies, as listed in error messages.
+    fn names() -> String {
+        Strategy::ALL.map(|strategy| strategy.name()).join(", ")
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("unknown strategy {0:?}, expected one of {}", Strategy::names())]
+pub struct UnknownStrategy(pub String);
+
+impl FromStr for Strategy {
+    type Err = UnknownStrategy;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL
+            .iter()
+            .find(|strategy| strategy.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownStrategy(name.into()))
+    }
+}
+
+pub(crate) fn two_pointer<T: Amount>(numbers: &[T], query: &Query<T>) -> Option<Vec<usize>> {
+    let sorted = Sorted::new(numbers);
+    let positions = sweep(&sorted, query, query.k, &mut Vec::new(), 0)?;
+
+    Some(
+        positions
+            .into_iter()
+            .map(|position| sorted.order[position])
+            .collect(),
+    )
+}
+
+/// Completes the entries picked in `prefix` with `k` more, at or after `start`.
+///
+/// The picked entries are kept rather than their sum, which may not fit into `T` even where the
+/// sum of all entries does.
+fn sweep<T: Amount>(
+    sorted: &Sorted<T>,
+    query: &Query<T>,
+    k: usize,
+    prefix: &mut Vec<T>,
+    start: usize,
+) -> Option<Vec<usize>> {
+    if k == 2 {
+        return sweep_pair(&sorted.values, query, prefix, start).map(|(low, high)| vec![low, high]);
+    }
+
+    for position in start..sorted.values.len() {
+        if sorted.overshoots(
+            prefix.iter().copied(),
+            position,
+            k,
+            query.with_replacement,
+            query.target,
+        ) {
+            break;
+        }
+        let next = if query.with_replacement {
+            position
+        } else {
+            position + 1
+        };
+
+        prefix.push(sorted.values[position]);
+        let positions = sweep(sorted, query, k - 1, prefix, next);
+        prefix.pop();
+        if let Some(mut positions) = positions {
+            positions.insert(0, position);
+            return Some(positions);
+        }
+    }
+
+    None
+}
+
+fn sweep_pair<T: Amount>(
+    values: &[T],
+    query: &Query<T>,
+    prefix: &[T],
+    start: usize,
+) -> Option<(usize, usize)> {
+    if start >= values.len() {
+        return None;
+    }
+
+    let (mut low, mut high) = (start, values.len() - 1);
+    while low < high || (query.with_replacement && low == high) {
+        let pair = [values[low], values[high]];
+
+        match compare_total(prefix.iter().copied().chain(pair), query.target) {
+            Ordering::Equal => return Some((low, high)),
+            Ordering::Less => low += 1,
+            Ordering::Greater if high == 0 => break,
+            Ordering::Greater => high -= 1,
+        }
+    }
+
+    None
+}
+
+pub(crate) fn hash_set<T: Amount>(numbers: &[T], query: &Query<T>) -> Option<Vec<usize>> {
+    lookup(numbers, query, query.k, &mut Vec::new(), 0)
+}
+
+fn lookup<T: Amount>(
+    numbers: &[T],
+    query: &Query<T>,
+    k: usize,
+    prefix: &mut Vec<T>,
+    start: usize,
+) -> Option<Vec<usize>> {
+    if k == 2 {
+        return lookup_pair(numbers, query, prefix, start)
+            .map(|(first, second)| vec![first, second]);
+    }
+
+    for index in start..numbers.len() {
+        let next = if query.with_replacement {
+            index
+        } else {
+            index + 1
+        };
+
+        prefix.push(numbers[index]);
+        let indices = lookup(numbers, query, k - 1, prefix, next);
+        prefix.pop();
+        if let Some(mut indices) = indices {
+            indices.insert(0, index);
+            return Some(indices);
+        }
+    }
+
+    None
+}
+
+fn lookup_pair<T: Amount>(
+    numbers: &[T],
+    query: &Query<T>,
+    prefix: &[T],
+    start: usize,
+) -> Option<(usize, usize)> {
+    let mut seen: HashMap<T, usize> = HashMap::new();
+
+    for (index, value) in numbers.iter().enumerate().skip(start) {
+        if query.with_replacement {
+            seen.entry(*value).or_insert(index);
+        }
+        let partner = remainder(
+            query.target,
+            prefix.iter().copied().chain(iter::once(*value)),
+        )
+        .ok();
+        if let Some(partner) = partner.and_then(|partner| seen.get(&partner)) {
+            return Some((*partner, index));
+        }
+        seen.entry(*value).or_insert(index);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::Query;
+    use crate::strategy::Strategy;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_parse_strategy_names() {
+        for strategy in Strategy::ALL.iter() {
+            assert_eq!(Strategy::from_str(&strategy.to_string()), Ok(*strategy))
+        }
+        assert_eq!(
+            Strategy::from_str("bogo-sort").unwrap_err().to_string(),
+            "unknown strategy \"bogo-sort\", expected one of binary-search, hash-set, two-pointer"
+        )
+    }
+
+    #[test]
+    fn should_find_aoc_matches_with_every_strategy() {
+        let numbers = vec![1721, 979, 366, 299, 675, 1456];
+
+        for strategy in Strategy::ALL.iter() {
+            let pair = Query::new(2, 2020).with_strategy(*strategy).find(&numbers);
+            let triple = Query::new(3, 2020).with_strategy(*strategy).find(&numbers);
+
+            assert_eq!(pair.unwrap().entries, vec![299, 1721], "{}", strategy);
+            assert_eq!(triple.unwrap().entries, vec![366, 675, 979], "{}", strategy);
+        }
+    }
+
+    #[test]
+    fn should_keep_entries_distinct_with_every_strategy() {
+        let numbers = vec![1010, 1721];
+
+        for strategy in Strategy::ALL.iter() {
+            let query = Query::new(2, 2020).with_strategy(*strategy);
+
+            assert_eq!(query.find(&numbers), None, "{}", strategy);
+            assert_eq!(
+                query.with_replacement(true).find(&numbers).unwrap().indices,
+                vec![0, 0],
+                "{}",
+                strategy
+            );
+        }
+    }
+
+    #[test]
+    fn should_match_signed_amounts_with_every_strategy() {
+        let numbers: Vec<i128> = vec![-300, 2500, 40, -480, 2800];
+
+        for strategy in Strategy::ALL.iter() {
+            let query = Query::new(3, 2020).with_strategy(*strategy);
+
+            assert_eq!(
+                query.find(&numbers).unwrap().entries,
+                vec![-480, -300, 2800],
+                "{}",
+                strategy
+            );
+        }
+    }
+
+    #[test]
+    fn should_agree_on_solvability() {
+        let numbers: Vec<usize> = (0..60).map(|i| (i * 37 + 11) % 101).collect();
+
+        for target in 0..250 {
+            for k in 2..=4 {
+                let expected = Query::new(k, target).find(&numbers).is_some();
+                for strategy in Strategy::ALL.iter() {
+                    let result = Query::new(k, target)
+                        .with_strategy(*strategy)
+                        .find(&numbers);
+
+                    assert_eq!(
+                        result.is_some(),
+                        expected,
+                        "{} k={} target={}",
+                        strategy,
+                        k,
+                        target
+                    );
+                    if let Some(result) = result {
+                        assert_eq!(result.entries.iter().sum::<usize>(), target);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_match_across_the_type_bounds_with_every_strategy() {
+        let large = [i64::MAX - 10, 20, -15];
+        let low = [i64::MIN, -1, 100];
+        let small: [i8; 3] = [100, 60, -40];
+        let unsigned: [u8; 3] = [250, 5, 1];
+
+        for strategy in Strategy::ALL.iter() {
+            for order in [[0, 1, 2], [2, 1, 0], [1, 2, 0]] {
+                let large: Vec<i64> = order.iter().map(|index| large[*index]).collect();
+                let low: Vec<i64> = order.iter().map(|index| low[*index]).collect();
+                let small: Vec<i8> = order.iter().map(|index| small[*index]).collect();
+                let large = Query::new(3, i64::MAX - 5)
+                    .with_strategy(*strategy)
+                    .find(&large);
+                let low = Query::new(3, i64::MIN + 99)
+                    .with_strategy(*strategy)
+                    .find(&low);
+                let small = Query::new(3, 120).with_strategy(*strategy).find(&small);
+
+                assert_eq!(
+                    large.unwrap().entries,
+                    vec![-15, 20, i64::MAX - 10],
+                    "{} {:?}",
+                    strategy,
+                    order
+                );
+                assert_eq!(
+                    low.unwrap().entries,
+                    vec![i64::MIN, -1, 100],
+                    "{} {:?}",
+                    strategy,
+                    order
+                );
+                assert_eq!(
+                    small.unwrap().entries,
+                    vec![-40, 60, 100],
+                    "{} {:?}",
+                    strategy,
+                    order
+                );
+            }
+            let query = Query::new(2, 255u8).with_strategy(*strategy);
+            assert_eq!(query.find(&unsigned).unwrap().entries, vec![5, 250]);
+            assert_eq!(query.with_replacement(true).find(&[200u8]), None)
+        }
+    }
+}
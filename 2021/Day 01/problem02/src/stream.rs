@@ -0,0 +1,190 @@
+use crate::amount::{checked_product, Amount};
+use crate::parse::{parse_line, InvalidEntry, Strictness};
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+/// An entry together with the 1-based line it was read from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Sighting<T> {
+    pub line: usize,
+    pub value: T,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StreamedPair<T> {
+    pub first: Sighting<T>,
+    pub second: Sighting<T>,
+}
+
+impl<T: Amount> StreamedPair<T> {
+    pub fn product(&self) -> Option<T> {
+        checked_product([self.first.value, self.second.value])
+    }
+}
+
+/// Online pair matcher, remembering every value seen so far.
+///
+/// A pair is reported as soon as its second member is pushed.
+#[derive(Debug)]
+pub struct PairStream<T> {
+    target: T,
+    with_replacement: bool,
+    seen: HashMap<T, usize>,
+}
+
+impl<T: Amount> PairStream<T> {
+    pub fn new(target: T) -> Self {
+        PairStream {
+            target,
+            with_replacement: false,
+            seen: HashMap::new(),
+        }
+    }
+
+    pub fn with_replacement(mut self, with_replacement: bool) -> Self {
+        self.with_replacement = with_replacement;
+        self
+    }
+
+    pub fn push(&mut self, line: usize, value: T) -> Option<StreamedPair<T>> {
+        let second = Sighting { line, value };
+        let partner = self.target.checked_sub(value)?;
+
+        if let Some(partner_line) = self.seen.get(&partner) {
+            let first = Sighting {
+                line: *partner_line,
+                value: partner,
+            };
+            return Some(StreamedPair { first, second });
+        }
+        if self.with_replacement && partner == value {
+            return Some(StreamedPair {
+                first: second,
+                second,
+            });
+        }
+
+        self.seen.entry(value).or_insert(line);
+        None
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("could not read input: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Invalid(#[from] InvalidEntry),
+}
+
+/// Reads `reader` line by line and stops at the first pair adding up to the stream's target.
+///
+/// Invalid entries fail in strict mode, in lenient mode they are handed to `skipped`.
+pub fn first_pair<T: Amount, R: BufRead>(
+    reader: R,
+    mut stream: PairStream<T>,
+    strictness: Strictness,
+    mut skipped: impl FnMut(InvalidEntry),
+) -> Result<Option<StreamedPair<T>>, StreamError> {
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        for entry in parse_line(line_index + 1, &line) {
+            match (entry, strictness) {
+                (Ok(value), _) => {
+                    if let Some(pair) = stream.push(line_index + 1, value) {
+                        return Ok(Some(pair));
+                    }
+                }
+                (Err(invalid), Strictness::Strict) => return Err(invalid.into()),
+                (Err(invalid), Strictness::Lenient) => skipped(invalid),
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::Strictness;
+    use crate::stream::{first_pair, PairStream, Sighting, StreamError, StreamedPair};
+    use std::io::{BufRead, BufReader, Read};
+
+    #[test]
+    fn should_answer_when_second_member_arrives() {
+        let mut stream = PairStream::new(2020);
+
+        assert_eq!(stream.push(1, 1721), None);
+        assert_eq!(stream.push(2, 979), None);
+        assert_eq!(
+            stream.push(3, 299),
+            Some(StreamedPair {
+                first: Sighting {
+                    line: 1,
+                    value: 1721
+                },
+                second: Sighting {
+                    line: 3,
+                    value: 299
+                },
+            })
+        )
+    }
+
+    #[test]
+    fn should_not_pair_an_entry_with_itself() {
+        let mut stream = PairStream::new(2020);
+        let mut replacing = PairStream::new(2020).with_replacement(true);
+
+        assert_eq!(stream.push(1, 1010), None);
+        assert_eq!(stream.push(2, 1010).unwrap().first.line, 1);
+        assert_eq!(replacing.push(1, 1010).unwrap().second.line, 1)
+    }
+
+    #[test]
+    fn should_stop_reading_after_the_pair() {
+        let input = "1721\n979\n299\nnot read\n";
+        let mut reader = BufReader::new(input.as_bytes());
+        let pair = first_pair(
+            reader.by_ref(),
+            PairStream::new(2020),
+            Strictness::Strict,
+            |_| {},
+        );
+
+        assert_eq!(pair.unwrap().unwrap().second.line, 3);
+        assert_eq!(reader.lines().next().unwrap().unwrap(), "not read")
+    }
+
+    #[test]
+    fn should_fail_on_invalid_entry_in_strict_mode() {
+        let input = "1721\nabc\n299\n";
+        let result = first_pair(
+            input.as_bytes(),
+            PairStream::new(2020),
+            Strictness::Strict,
+            |_| {},
+        );
+
+        match result {
+            Err(StreamError::Invalid(invalid)) => assert_eq!(invalid.line, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_skip_invalid_entry_in_lenient_mode() {
+        let input = "1721\nabc\n299\n";
+        let mut skipped = Vec::new();
+        let result = first_pair(
+            input.as_bytes(),
+            PairStream::new(2020),
+            Strictness::Lenient,
+            |invalid| skipped.push(invalid.line),
+        );
+
+        assert_eq!(result.unwrap().unwrap().second.line, 3);
+        assert_eq!(skipped, vec![2])
+    }
+}
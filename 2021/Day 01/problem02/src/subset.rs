@@ -0,0 +1,209 @@
+use crate::amount::Amount;
+use crate::search::SearchResult;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Entries picked on the way to a sum, newest first, shared between all sums derived from it.
+struct Link {
+    index: usize,
+    previous: Option<Rc<Link>>,
+}
+
+#[derive(Clone)]
+struct Path {
+    count: usize,
+    last: Rc<Link>,
+}
+
+impl Path {
+    fn start(index: usize) -> Self {
+        Path {
+            count: 1,
+            last: Rc::new(Link {
+                index,
+                previous: None,
+            }),
+        }
+    }
+
+    fn extend(&self, index: usize) -> Self {
+        Path {
+            count: self.count + 1,
+            last: Rc::new(Link {
+                index,
+                previous: Some(self.last.clone()),
+            }),
+        }
+    }
+
+    fn indices(&self) -> Vec<usize> {
+        let mut indices = Vec::with_capacity(self.count);
+        let mut link = Some(&self.last);
+        while let Some(current) = link {
+            indices.push(current.index);
+            link = current.previous.as_ref();
+        }
+        indices
+    }
+}
+
+/// A sum that may leave the range of `T`, kept as the number of times it wrapped around the range
+/// and the wrapped value. Ordered like the sum itself.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct Wide<T> {
+    wraps: isize,
+    value: T,
+}
+
+impl<T: Amount> Wide<T> {
+    fn new(value: T) -> Self {
+        Wide { wraps: 0, value }
+    }
+
+    fn add(self, other: Wide<T>) -> Self {
+        let (value, wrapped) = self.value.overflowing_add(other.value);
+        let carry = match (wrapped, other.value < T::ZERO) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => -1,
+        };
+
+        Wide {
+            wraps: self.wraps + other.wraps + carry,
+            value,
+        }
+    }
+}
+
+/// Range of sums the entries after a position can still add to a partial sum.
+struct Reach<T> {
+    negative: Vec<Wide<T>>,
+    positive: Vec<Wide<T>>,
+}
+
+impl<T: Amount> Reach<T> {
+    fn new(numbers: &[T]) -> Self {
+        let mut negative = vec![Wide::new(T::ZERO); numbers.len() + 1];
+        let mut positive = vec![Wide::new(T::ZERO); numbers.len() + 1];
+
+        for (start, value) in numbers.iter().enumerate().rev() {
+            let (grown, kept) = if *value < T::ZERO {
+                (&mut negative, &mut positive)
+            } else {
+                (&mut positive, &mut negative)
+            };
+            grown[start] = grown[start + 1].add(Wide::new(*value));
+            kept[start] = kept[start + 1];
+        }
+
+        Reach { negative, positive }
+    }
+
+    /// Whether `sum` can still grow or shrink to `target` with the entries from `start` on.
+    fn allows(&self, start: usize, sum: Wide<T>, target: Wide<T>) -> bool {
+        sum.add(self.negative[start]) <= target && target <= sum.add(self.positive[start])
+    }
+}
+
+/// Finds a non-empty subset of `numbers`, of any size, adding up to `target`.
+///
+/// Dynamic programming over every reachable sum, remembering the fewest entries each sum can be
+/// made of. The returned subset therefore has minimum cardinality, ties are broken in favour of
+/// the subset found first. Its entries are ordered by ascending value.
+///
+/// Partial sums are kept even where they leave the range of `T`, as later refunds may bring them
+/// back to the target.
+pub fn subset_sum<T: Amount>(numbers: &[T], target: T) -> Option<SearchResult<T>> {
+    let reach = Reach::new(numbers);
+    let target = Wide::new(target);
+    let mut paths: BTreeMap<Wide<T>, Path> = BTreeMap::new();
+
+    for (index, value) in numbers.iter().enumerate() {
+        let value = Wide::new(*value);
+        let mut candidates: Vec<(Wide<T>, Path)> = paths
+            .iter()
+            .map(|(sum, path)| (sum.add(value), path.extend(index)))
+            .collect();
+        candidates.push((value, Path::start(index)));
+
+        for (sum, path) in candidates {
+            if !reach.allows(index + 1, sum, target) {
+                continue;
+            }
+            match paths.get(&sum) {
+                Some(existing) if existing.count <= path.count => {}
+                _ => {
+                    paths.insert(sum, path);
+                }
+            }
+        }
+    }
+
+    paths.get(&target).map(|path| {
+        let mut indices = path.indices();
+        indices.sort_by_key(|index| (numbers[*index], *index));
+        SearchResult::new(numbers, target.value, indices)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subset::subset_sum;
+
+    #[test]
+    fn should_find_aoc_pair_as_smallest_subset() {
+        let numbers = vec![1721, 979, 366, 299, 675, 1456];
+        let result = subset_sum(&numbers, 2020).unwrap();
+
+        assert_eq!(result.entries, vec![299, 1721])
+    }
+
+    #[test]
+    fn should_find_subset_of_any_size() {
+        let numbers = vec![3, 5, 11, 17, 23];
+        let result = subset_sum(&numbers, 3 + 5 + 11 + 23).unwrap();
+
+        assert_eq!(result.entries, vec![3, 5, 11, 23]);
+        assert_eq!(result.indices, vec![0, 1, 2, 4])
+    }
+
+    #[test]
+    fn should_prefer_fewest_entries() {
+        let numbers = vec![1, 1, 1, 1, 4];
+
+        assert_eq!(subset_sum(&numbers, 4).unwrap().entries, vec![4])
+    }
+
+    #[test]
+    fn should_balance_refunds() {
+        let numbers: Vec<i64> = vec![-5, 3, 10, 2];
+
+        assert_eq!(subset_sum(&numbers, 0).unwrap().entries, vec![-5, 2, 3]);
+        assert_eq!(subset_sum(&numbers, 10).unwrap().entries, vec![10])
+    }
+
+    #[test]
+    fn should_not_count_the_empty_subset() {
+        let numbers = vec![2, 4];
+
+        assert_eq!(subset_sum(&numbers, 0), None);
+        assert_eq!(subset_sum(&numbers, 3), None)
+    }
+
+    #[test]
+    fn should_follow_running_sums_beyond_the_bounds() {
+        for numbers in [[i64::MAX - 10, 20, -15], [-15, 20, i64::MAX - 10]] {
+            assert_eq!(
+                subset_sum(&numbers, i64::MAX - 5).unwrap().entries,
+                vec![-15, 20, i64::MAX - 10]
+            )
+        }
+        assert_eq!(
+            subset_sum(&[i64::MIN, -1, 100], i64::MIN + 99)
+                .unwrap()
+                .entries,
+            vec![i64::MIN, -1, 100]
+        );
+        assert_eq!(subset_sum(&[200u8, 100], 44), None)
+    }
+}
@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use password_policy::{
+    count_valid, tally, tally_file, Grammar, PasswordLine, PasswordPolicy, Toboggan,
+};
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+/// Generates a database of `size` lines of random lowercase passwords.
+fn generate(size: usize) -> String {
+    let mut state: u64 = 0x2545_f491;
+    let mut next = |bound: u64| {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1);
+        (state >> 33) % bound
+    };
+    let mut input = String::new();
+
+    for _ in 0..size {
+        let length = 6 + next(14) as usize;
+        let min = 1 + next(length as u64 / 2) as usize;
+        let max = min + 1 + next((length - min) as u64) as usize;
+        let mut letters: Vec<char> = (0..=length)
+            .map(|_| char::from(b'a' + next(26) as u8))
+            .collect();
+        let letter = letters.pop().expect("one letter more than the password");
+        let password: String = letters.into_iter().collect();
+        input.push_str(&format!("{}-{} {}: {}\n", min, max, letter, password));
+    }
+    input
+}
+
+fn validation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validate");
+    group.sample_size(10);
+    let grammar = Grammar::default();
+    let policy = Toboggan::default();
+
+    for exponent in 4..=6 {
+        let size = 10usize.pow(exponent);
+        let input = generate(size);
+        let path: PathBuf =
+            env::temp_dir().join(format!("scanning-{}-{}.txt", process::id(), size));
+        fs::write(&path, &input).expect("Could not write database!");
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("owned", size), &input, |b, input| {
+            b.iter(|| {
+                let lines: Vec<PasswordLine> = input
+                    .lines()
+                    .enumerate()
+                    .filter_map(|(index, text)| grammar.parse(index + 1, text).ok())
+                    .map(PasswordLine::into_owned)
+                    .collect();
+                count_valid(&policy, &lines)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("regex", size), &input, |b, input| {
+            b.iter(|| count_valid(&policy, &grammar.extract(input).0))
+        });
+        group.bench_with_input(BenchmarkId::new("scanner", size), &input, |b, input| {
+            b.iter(|| {
+                input
+                    .lines()
+                    .enumerate()
+                    .filter_map(|(index, text)| grammar.scan(index + 1, text).ok())
+                    .filter(|line| policy.is_valid(line))
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", size), &input, |b, input| {
+            b.iter(|| tally(input.as_bytes(), &grammar, &policy).unwrap().valid)
+        });
+        group.bench_with_input(BenchmarkId::new("mapped", size), &path, |b, path| {
+            b.iter(|| tally_file(path, &grammar, &policy).unwrap().valid)
+        });
+
+        fs::remove_file(&path).expect("Could not remove database!");
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, validation);
+criterion_main!(benches);
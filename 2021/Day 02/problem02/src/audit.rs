@@ -0,0 +1,309 @@
+use crate::line::Grammar;
+use crate::policy::{Observation, PasswordPolicy};
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Valid,
+    Invalid,
+    /// The line could not be read, see [`crate::PasswordLineError`].
+    Unreadable,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Valid => "valid",
+            Status::Invalid => "invalid",
+            Status::Unreadable => "unreadable",
+        }
+    }
+}
+
+/// Verdict on one line of the password database.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct AuditEntry {
+    pub line: usize,
+    pub policy: String,
+    /// The line's policy as written, e.g. `1-3 a`.
+    pub rule: Option<String>,
+    pub password: Option<String>,
+    pub status: Status,
+    pub observed: Option<Observation>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct Summary {
+    pub lines: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub unreadable: usize,
+}
+
+/// Checks every line of `input` against `policy`, keeping unreadable lines in the report.
+pub fn audit(input: &str, grammar: &Grammar, policy: &dyn PasswordPolicy) -> Vec<AuditEntry> {
+    grammar
+        .lines(input)
+        .map(|(number, parsed)| match parsed {
+            Ok(line) => {
+                let verdict = policy.audit(&line);
+
+                AuditEntry {
+                    line: number,
+                    policy: policy.name().into(),
+                    rule: Some(format!("{}-{} {}", line.min, line.max, line.letter)),
+                    password: Some(line.password.into_owned()),
+                    status: if verdict.valid {
+                        Status::Valid
+                    } else {
+                        Status::Invalid
+                    },
+                    observed: verdict.observed,
+                    reason: verdict.reason,
+                }
+            }
+            Err(error) => AuditEntry {
+                line: number,
+                policy: policy.name().into(),
+                rule: None,
+                password: None,
+                status: Status::Unreadable,
+                observed: None,
+                reason: Some(error.to_string()),
+            },
+        })
+        .collect()
+}
+
+pub fn summarize(entries: &[AuditEntry]) -> Summary {
+    let mut summary = Summary {
+        lines: entries.len(),
+        ..Summary::default()
+    };
+    for entry in entries {
+        match entry.status {
+            Status::Valid => summary.valid += 1,
+            Status::Invalid => summary.invalid += 1,
+            Status::Unreadable => summary.unreadable += 1,
+        }
+    }
+    summary
+}
+
+/// How the result of a check is printed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ReportFormat {
+    /// Only the number of valid lines.
+    #[default]
+    Count,
+    /// One JSON object per line, the last one holding the summary.
+    JsonLines,
+    /// A header and one row per line, followed by a summary table.
+    Csv,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 3] = [
+        ReportFormat::Count,
+        ReportFormat::JsonLines,
+        ReportFormat::Csv,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportFormat::Count => "count",
+            ReportFormat::JsonLines => "jsonl",
+            ReportFormat::Csv => "csv",
+        }
+    }
+
+    /// Names of all formats, as listed in error messages.
+    fn names() -> String {
+        ReportFormat::ALL.map(|format| format.name()).join(", ")
+    }
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("unknown format {0:?}, expected one of {}", ReportFormat::names())]
+pub struct UnknownFormat(pub String);
+
+impl FromStr for ReportFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ReportFormat::ALL
+            .iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownFormat(name.into()))
+    }
+}
+
+pub fn write_report<W: Write>(
+    out: &mut W,
+    format: ReportFormat,
+    entries: &[AuditEntry],
+) -> io::Result<()> {
+    let summary = summarize(entries);
+
+    match format {
+        ReportFormat::Count => writeln!(out, "{}", summary.valid),
+        ReportFormat::JsonLines => {
+            #[derive(Serialize)]
+            struct Footer {
+                summary: Summary,
+            }
+
+            for entry in entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                writeln!(out)?;
+            }
+            serde_json::to_writer(&mut *out, &Footer { summary })?;
+            writeln!(out)
+        }
+        ReportFormat::Csv => {
+            writeln!(out, "line,policy,rule,password,status,observed,reason")?;
+            for entry in entries {
+                let observed = entry.observed.as_ref().map(Observation::to_string);
+                let fields = [
+                    entry.line.to_string(),
+                    csv_field(&entry.policy),
+                    csv_field(entry.rule.as_deref().unwrap_or("")),
+                    csv_field(entry.password.as_deref().unwrap_or("")),
+                    entry.status.name().to_string(),
+                    csv_field(observed.as_deref().unwrap_or("")),
+                    csv_field(entry.reason.as_deref().unwrap_or("")),
+                ];
+                writeln!(out, "{}", fields.join(","))?;
+            }
+            writeln!(out)?;
+            writeln!(out, "lines,valid,invalid,unreadable")?;
+            writeln!(
+                out,
+                "{},{},{},{}",
+                summary.lines, summary.valid, summary.invalid, summary.unreadable
+            )
+        }
+    }
+}
+
+/// Quotes a field containing separators, quotes or line breaks.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::{audit, summarize, write_report, ReportFormat, Status, Summary};
+    use crate::fixtures::WITH_UNREADABLE_LINE;
+    use crate::line::Grammar;
+    use crate::policy::{Sled, Toboggan};
+
+    fn report(format: ReportFormat) -> String {
+        let mut out = Vec::new();
+        write_report(
+            &mut out,
+            format,
+            &audit(WITH_UNREADABLE_LINE, &Grammar::default(), &Sled::default()),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn should_audit_every_line() {
+        let entries = audit(
+            WITH_UNREADABLE_LINE,
+            &Grammar::default(),
+            &Toboggan::default(),
+        );
+        let statuses: Vec<Status> = entries.iter().map(|entry| entry.status).collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Valid,
+                Status::Invalid,
+                Status::Unreadable,
+                Status::Invalid
+            ]
+        );
+        assert_eq!(
+            entries[2].reason.as_deref(),
+            Some("line 3: \"oops\" does not look like `min-max letter: password`")
+        );
+        assert_eq!(
+            summarize(&entries),
+            Summary {
+                lines: 4,
+                valid: 1,
+                invalid: 2,
+                unreadable: 1
+            }
+        )
+    }
+
+    #[test]
+    fn should_write_json_lines_with_summary() {
+        let report = report(ReportFormat::JsonLines);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(
+            lines[1],
+            r#"{"line":2,"policy":"sled","rule":"1-3 b","password":"cdefg","status":"invalid","observed":{"count":0},"reason":"'b' occurs 0 times, expected 1 to 3"}"#
+        );
+        assert_eq!(
+            lines[4],
+            r#"{"summary":{"lines":4,"valid":2,"invalid":1,"unreadable":1}}"#
+        )
+    }
+
+    #[test]
+    fn should_write_csv_with_summary() {
+        let report = report(ReportFormat::Csv);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "line,policy,rule,password,status,observed,reason");
+        assert_eq!(lines[1], "1,sled,1-3 a,abcde,valid,count 1,");
+        assert_eq!(
+            lines[3],
+            "3,sled,,,unreadable,,\"line 3: \"\"oops\"\" does not look like `min-max letter: password`\""
+        );
+        assert_eq!(
+            &lines[5..],
+            &["", "lines,valid,invalid,unreadable", "4,2,1,1"]
+        )
+    }
+
+    #[test]
+    fn should_parse_format_names() {
+        for format in ReportFormat::ALL.iter() {
+            assert_eq!(format.name().parse(), Ok(*format))
+        }
+        assert_eq!(
+            "xml".parse::<ReportFormat>().unwrap_err().to_string(),
+            "unknown format \"xml\", expected one of count, jsonl, csv"
+        )
+    }
+
+    #[test]
+    fn should_keep_plain_count() {
+        assert_eq!(report(ReportFormat::Count), "2\n")
+    }
+}
@@ -0,0 +1,202 @@
+use crate::line::{Grammar, PasswordLineError};
+use crate::policy::PasswordPolicy;
+use std::fmt::{self, Display, Formatter};
+
+/// A line whose verdict differs between two policies.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    pub line: usize,
+    /// The line's policy as written, e.g. `1-3 a`.
+    pub rule: String,
+    pub password: String,
+    /// Verdict of the policy migrated from.
+    pub before: bool,
+    /// Verdict of the policy migrated to.
+    pub after: bool,
+}
+
+/// Verdicts of two policies on the same database, as a confusion matrix.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Comparison {
+    pub before: String,
+    pub after: String,
+    /// Number of lines by verdict, indexed by `[before as usize][after as usize]`.
+    pub matrix: [[usize; 2]; 2],
+    pub changes: Vec<Change>,
+    pub skipped: Vec<PasswordLineError>,
+}
+
+impl Comparison {
+    /// Number of lines valid or not under each policy.
+    pub fn count(&self, before: bool, after: bool) -> usize {
+        self.matrix[before as usize][after as usize]
+    }
+}
+
+/// Checks every readable line of `input` against both policies, as when migrating from `before`
+/// to `after`.
+pub fn compare(
+    input: &str,
+    grammar: &Grammar,
+    before: &dyn PasswordPolicy,
+    after: &dyn PasswordPolicy,
+) -> Comparison {
+    let mut comparison = Comparison {
+        before: before.name().into(),
+        after: after.name().into(),
+        matrix: [[0; 2]; 2],
+        changes: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for (number, parsed) in grammar.lines(input) {
+        let line = match parsed {
+            Ok(line) => line,
+            Err(error) => {
+                comparison.skipped.push(error);
+                continue;
+            }
+        };
+        let was_valid = before.is_valid(&line);
+        let is_valid = after.is_valid(&line);
+
+        comparison.matrix[was_valid as usize][is_valid as usize] += 1;
+        if was_valid != is_valid {
+            comparison.changes.push(Change {
+                line: number,
+                rule: format!("{}-{} {}", line.min, line.max, line.letter),
+                password: line.password.into_owned(),
+                before: was_valid,
+                after: is_valid,
+            });
+        }
+    }
+
+    comparison
+}
+
+fn verdict(valid: bool) -> &'static str {
+    if valid {
+        "valid"
+    } else {
+        "invalid"
+    }
+}
+
+impl Display for Comparison {
+    /// The matrix with the policy migrated from in rows, followed by the changed lines.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let corner = format!("{} \\ {}", self.before, self.after);
+        let width = self
+            .matrix
+            .iter()
+            .flatten()
+            .map(|count| count.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(verdict(false).len());
+        let label = corner.len().max(verdict(false).len());
+
+        writeln!(
+            f,
+            "{:<label$}  {:>width$}  {:>width$}",
+            corner,
+            verdict(true),
+            verdict(false),
+            label = label,
+            width = width
+        )?;
+        for before in [true, false] {
+            writeln!(
+                f,
+                "{:<label$}  {:>width$}  {:>width$}",
+                verdict(before),
+                self.count(before, true),
+                self.count(before, false),
+                label = label,
+                width = width
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{} lines change verdict", self.changes.len())?;
+        for change in &self.changes {
+            writeln!(
+                f,
+                "  line {}: {}: {} {} -> {}",
+                change.line,
+                change.rule,
+                change.password,
+                verdict(change.before),
+                verdict(change.after)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare::compare;
+    use crate::fixtures::WITH_UNREADABLE_LINE;
+    use crate::line::Grammar;
+    use crate::policy::{Sled, Toboggan};
+
+    #[test]
+    fn should_fill_confusion_matrix() {
+        let comparison = compare(
+            &input(),
+            &Grammar::default(),
+            &Sled::default(),
+            &Toboggan::default(),
+        );
+
+        assert_eq!(comparison.matrix, [[1, 1], [1, 2]]);
+        assert_eq!(comparison.count(true, false), 1);
+        assert_eq!(comparison.count(true, true), 2);
+        assert_eq!(comparison.skipped.len(), 1)
+    }
+
+    #[test]
+    fn should_list_changed_lines() {
+        let comparison = compare(
+            &input(),
+            &Grammar::default(),
+            &Sled::default(),
+            &Toboggan::default(),
+        );
+        let changed: Vec<(usize, bool)> = comparison
+            .changes
+            .iter()
+            .map(|change| (change.line, change.after))
+            .collect();
+
+        assert_eq!(changed, vec![(4, false), (5, true)]);
+        assert_eq!(comparison.changes[0].rule, "2-9 c")
+    }
+
+    #[test]
+    fn should_print_matrix_and_changes() {
+        let comparison = compare(
+            &input(),
+            &Grammar::default(),
+            &Sled::default(),
+            &Toboggan::default(),
+        );
+
+        assert_eq!(
+            comparison.to_string(),
+            "sled \\ toboggan    valid  invalid\n\
+             valid                  2        1\n\
+             invalid                1        1\n\
+             \n\
+             2 lines change verdict\n  \
+             line 4: 2-9 c: ccccccccc valid -> invalid\n  \
+             line 5: 1-1 d: dd invalid -> valid\n"
+        )
+    }
+
+    fn input() -> String {
+        format!("{}1-1 d: dd\n1-2 f: fx\n", WITH_UNREADABLE_LINE)
+    }
+}
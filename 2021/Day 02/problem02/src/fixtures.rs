@@ -0,0 +1,8 @@
+//! Password databases shared by the tests.
+
+/// The example database of the puzzle.
+pub(crate) const AOC_EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+/// [`AOC_EXAMPLE`] with an unreadable third line.
+pub(crate) const WITH_UNREADABLE_LINE: &str =
+    "1-3 a: abcde\n1-3 b: cdefg\noops\n2-9 c: ccccccccc\n";
@@ -0,0 +1,23 @@
+pub mod audit;
+pub mod compare;
+#[cfg(test)]
+mod fixtures;
+pub mod line;
+pub mod mapped;
+pub mod policy;
+pub mod repair;
+pub mod rules;
+pub mod segment;
+pub mod stats;
+
+pub use audit::{audit, write_report, AuditEntry, ReportFormat, Status, Summary};
+pub use compare::{compare, Change, Comparison};
+pub use line::{extract_password_lines, Grammar, PasswordLine, PasswordLineError};
+pub use mapped::{tally, tally_file, Tally};
+pub use policy::{
+    builtin, count_valid, Observation, PasswordPolicy, Sled, Toboggan, UnknownPolicy, Verdict,
+};
+pub use repair::{edit_distance, suggest, Repair, Suggestion};
+pub use rules::{Rule, RuleError, RulePolicy};
+pub use segment::{Segmentation, UnknownSegmentation};
+pub use stats::{strength, survey, CharClass, Corpus, Rating};
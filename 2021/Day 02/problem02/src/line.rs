@@ -0,0 +1,443 @@
+use crate::segment::Segmentation;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::borrow::Cow;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A line of the password database: the corporate policy and the password it applies to.
+///
+/// How `min`, `max` and `letter` are read depends on the [`crate::PasswordPolicy`]. The letter may
+/// be longer than one character, it is then matched as a whole. Letter and password borrow from
+/// the parsed text unless they had to be unquoted.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PasswordLine<'a> {
+    pub min: usize,
+    pub max: usize,
+    pub letter: Cow<'a, str>,
+    pub password: Cow<'a, str>,
+    /// Whether the letter matches the password regardless of case, see [`Grammar::ignoring_case`].
+    pub ignore_case: bool,
+}
+
+lazy_static! {
+    static ref DEFAULT_GRAMMAR: Grammar = Grammar::default();
+}
+
+/// Layout of the lines of a password database, `min-max letter: password` by default.
+///
+/// Letter and password may be enclosed in double quotes, with `\"` and `\\` as escapes, to hold
+/// the separators or leading and trailing spaces.
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    range_separator: String,
+    letter_separator: String,
+    password_separator: String,
+    ignore_case: bool,
+    pattern: Regex,
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        Grammar::new("-", " ", ": ")
+    }
+}
+
+impl Grammar {
+    /// Lines of the form `min` `range_separator` `max` `letter_separator` `letter`
+    /// `password_separator` `password`.
+    pub fn new(range_separator: &str, letter_separator: &str, password_separator: &str) -> Self {
+        let quoted = r#""(?:[^"\\]|\\.)*""#;
+        let pattern = format!(
+            r"^(?P<min>\d+){}(?P<max>\d+){}(?P<letter>{}|.+?){}(?P<password>{}|.*)$",
+            regex::escape(range_separator),
+            regex::escape(letter_separator),
+            quoted,
+            regex::escape(password_separator),
+            quoted,
+        );
+
+        Grammar {
+            range_separator: range_separator.into(),
+            letter_separator: letter_separator.into(),
+            password_separator: password_separator.into(),
+            ignore_case: false,
+            pattern: Regex::new(&pattern).expect("separators are escaped"),
+        }
+    }
+
+    /// Matches letters regardless of case.
+    pub fn ignoring_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// The layout of a line, for error messages.
+    pub fn format(&self) -> String {
+        format!(
+            "min{}max{}letter{}password",
+            self.range_separator, self.letter_separator, self.password_separator
+        )
+    }
+
+    fn no_match(&self, line: usize, text: &str) -> PasswordLineError {
+        PasswordLineError::NoMatch {
+            line,
+            text: text.into(),
+            format: self.format(),
+        }
+    }
+
+    /// Parses the line with 1-based number `line`.
+    pub fn parse<'a>(
+        &self,
+        line: usize,
+        text: &'a str,
+    ) -> Result<PasswordLine<'a>, PasswordLineError> {
+        let c = self
+            .pattern
+            .captures(text)
+            .ok_or_else(|| self.no_match(line, text))?;
+        let field = |name: &str| c.name(name).expect("all groups take part").as_str();
+
+        self.read(
+            line,
+            text,
+            [
+                field("min"),
+                field("max"),
+                field("letter"),
+                field("password"),
+            ],
+        )
+    }
+
+    /// Parses the line like [`Grammar::parse`] with a hand-written scanner instead of the regular
+    /// expression, only taking ASCII digits as bounds.
+    pub fn scan<'a>(
+        &self,
+        line: usize,
+        text: &'a str,
+    ) -> Result<PasswordLine<'a>, PasswordLineError> {
+        let fields = (|| {
+            let (min, rest) = digits(text)?;
+            let rest = rest.strip_prefix(self.range_separator.as_str())?;
+            let (max, rest) = digits(rest)?;
+            let rest = rest.strip_prefix(self.letter_separator.as_str())?;
+            let (letter, password) = self.split_letter(rest)?;
+            Some([min, max, letter, password])
+        })();
+
+        match fields {
+            Some(fields) => self.read(line, text, fields),
+            None => Err(self.no_match(line, text)),
+        }
+    }
+
+    /// Splits `text` at the first password separator after the letter, which is either quoted or
+    /// at least one character long.
+    fn split_letter<'a>(&self, text: &'a str) -> Option<(&'a str, &'a str)> {
+        let separator = self.password_separator.as_str();
+        if let Some(end) = quote_end(text) {
+            if let Some(password) = text[end..].strip_prefix(separator) {
+                return Some((&text[..end], password));
+            }
+        }
+
+        let first = text.chars().next()?.len_utf8();
+        let at = first + text[first..].find(separator)?;
+        Some((&text[..at], &text[at + separator.len()..]))
+    }
+
+    /// Builds the line out of its `[min, max, letter, password]` fields as written in `text`.
+    fn read<'a>(
+        &self,
+        line: usize,
+        text: &'a str,
+        [min, max, letter, password]: [&'a str; 4],
+    ) -> Result<PasswordLine<'a>, PasswordLineError> {
+        let bound = |bound: &str| {
+            usize::from_str(bound).map_err(|source| PasswordLineError::BadBound {
+                line,
+                bound: bound.into(),
+                source,
+            })
+        };
+        let min = bound(min)?;
+        let max = bound(max)?;
+        if min > max {
+            return Err(PasswordLineError::MinAboveMax { line, min, max });
+        }
+
+        let letter = unquote(letter).ok_or(PasswordLineError::UnterminatedQuote { line })?;
+        let password = unquote(password).ok_or(PasswordLineError::UnterminatedQuote { line })?;
+        if letter.is_empty() {
+            return Err(self.no_match(line, text));
+        }
+        if password.is_empty() {
+            return Err(PasswordLineError::EmptyPassword { line });
+        }
+
+        Ok(PasswordLine {
+            min,
+            max,
+            letter,
+            password,
+            ignore_case: self.ignore_case,
+        })
+    }
+
+    /// Parses every line of `input`, yielding each with its 1-based line number.
+    pub fn lines<'g, 'a: 'g>(
+        &'g self,
+        input: &'a str,
+    ) -> impl Iterator<Item = (usize, Result<PasswordLine<'a>, PasswordLineError>)> + 'g {
+        input
+            .lines()
+            .enumerate()
+            .map(move |(index, text)| (index + 1, self.parse(index + 1, text)))
+    }
+
+    /// Parses every line, collecting the lines that could not be read instead of stopping at them.
+    pub fn extract<'a>(&self, input: &'a str) -> (Vec<PasswordLine<'a>>, Vec<PasswordLineError>) {
+        let mut password_lines = Vec::new();
+        let mut errors = Vec::new();
+
+        for (_, parsed) in self.lines(input) {
+            match parsed {
+                Ok(password_line) => password_lines.push(password_line),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        (password_lines, errors)
+    }
+}
+
+/// Splits the leading ASCII digits off `text`, `None` if there are none.
+fn digits(text: &str) -> Option<(&str, &str)> {
+    let end = text.bytes().take_while(u8::is_ascii_digit).count();
+    Some(text.split_at(end)).filter(|_| end > 0)
+}
+
+/// Byte offset right after the closing quote of `text`, `None` if it does not start with a quote
+/// or the quote is not closed.
+fn quote_end(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    if bytes.first() != Some(&b'"') {
+        return None;
+    }
+
+    let mut index = 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => return Some(index + 1),
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+/// Removes the quotes around `text` and resolves its escapes, `None` if a quote is not closed.
+fn unquote(text: &str) -> Option<Cow<'_, str>> {
+    let inner = match text.strip_prefix('"') {
+        Some(inner) => inner.strip_suffix('"').filter(|_| text.len() > 1)?,
+        None => return Some(Cow::Borrowed(text)),
+    };
+    if !inner.contains('\\') {
+        return Some(Cow::Borrowed(inner));
+    }
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            c => unquoted.push(c),
+        }
+    }
+    Some(Cow::Owned(unquoted))
+}
+
+/// Why a line of the password database could not be read, with its 1-based line number.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum PasswordLineError {
+    #[error("line {line}: {text:?} does not look like `{format}`")]
+    NoMatch {
+        line: usize,
+        text: String,
+        format: String,
+    },
+    #[error("line {line}: bound {bound:?} is out of range ({source})")]
+    BadBound {
+        line: usize,
+        bound: String,
+        #[source]
+        source: ParseIntError,
+    },
+    #[error("line {line}: minimum {min} is above maximum {max}")]
+    MinAboveMax { line: usize, min: usize, max: usize },
+    #[error("line {line}: password is empty")]
+    EmptyPassword { line: usize },
+    #[error("line {line}: quote is not closed")]
+    UnterminatedQuote { line: usize },
+}
+
+impl PasswordLineError {
+    pub fn line(&self) -> usize {
+        match self {
+            PasswordLineError::NoMatch { line, .. }
+            | PasswordLineError::BadBound { line, .. }
+            | PasswordLineError::MinAboveMax { line, .. }
+            | PasswordLineError::EmptyPassword { line }
+            | PasswordLineError::UnterminatedQuote { line } => *line,
+        }
+    }
+
+    /// Moves the error `lines` further down the database.
+    pub(crate) fn shift(&mut self, lines: usize) {
+        match self {
+            PasswordLineError::NoMatch { line, .. }
+            | PasswordLineError::BadBound { line, .. }
+            | PasswordLineError::MinAboveMax { line, .. }
+            | PasswordLineError::EmptyPassword { line }
+            | PasswordLineError::UnterminatedQuote { line } => *line += lines,
+        }
+    }
+}
+
+impl<'a> PasswordLine<'a> {
+    /// Parses the line with 1-based number `line` in the default grammar.
+    pub fn parse(line: usize, text: &'a str) -> Result<Self, PasswordLineError> {
+        DEFAULT_GRAMMAR.parse(line, text)
+    }
+
+    /// Copies letter and password, so that the line outlives the parsed text.
+    pub fn into_owned(self) -> PasswordLine<'static> {
+        PasswordLine {
+            letter: Cow::Owned(self.letter.into_owned()),
+            password: Cow::Owned(self.password.into_owned()),
+            ..self
+        }
+    }
+
+    /// 1-based positions of the non-overlapping occurrences of the letter in the password.
+    pub fn occurrences(&self, segmentation: Segmentation) -> Vec<usize> {
+        segmentation.occurrences(&self.password, &self.letter, self.ignore_case)
+    }
+
+    /// Whether the letter is in the password at 1-based `position`.
+    pub fn letter_at(&self, segmentation: Segmentation, position: usize) -> bool {
+        segmentation.letter_at(&self.password, &self.letter, position, self.ignore_case)
+    }
+}
+
+impl FromStr for PasswordLine<'static> {
+    type Err = PasswordLineError;
+
+    /// Parses `text` as the first line of a database in the default grammar.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        PasswordLine::parse(1, text).map(PasswordLine::into_owned)
+    }
+}
+
+/// Parses every line in the default grammar, see [`Grammar::extract`].
+pub fn extract_password_lines(input: &str) -> (Vec<PasswordLine<'_>>, Vec<PasswordLineError>) {
+    DEFAULT_GRAMMAR.extract(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::line::{extract_password_lines, Grammar, PasswordLine, PasswordLineError};
+    use std::str::FromStr;
+
+    #[test]
+    fn should_parse_password_line() {
+        let line = PasswordLine::from_str("1-3 a: abcde").unwrap();
+
+        assert_eq!((line.min, line.max, &*line.letter), (1, 3, "a"));
+        assert_eq!(line.password, "abcde")
+    }
+
+    #[test]
+    fn should_reject_malformed_lines() {
+        assert_eq!(
+            PasswordLine::parse(4, "1-3 a abcde").unwrap_err(),
+            PasswordLineError::NoMatch {
+                line: 4,
+                text: "1-3 a abcde".into(),
+                format: "min-max letter: password".into()
+            }
+        );
+        assert_eq!(
+            PasswordLine::parse(5, "3-1 a: abcde").unwrap_err(),
+            PasswordLineError::MinAboveMax {
+                line: 5,
+                min: 3,
+                max: 1
+            }
+        );
+        assert_eq!(
+            PasswordLine::parse(6, "1-3 a: ").unwrap_err(),
+            PasswordLineError::EmptyPassword { line: 6 }
+        )
+    }
+
+    #[test]
+    fn should_report_huge_bounds() {
+        let error = PasswordLine::from_str("1-99999999999999999999 a: abcde").unwrap_err();
+
+        assert!(matches!(error, PasswordLineError::BadBound { line: 1, .. }));
+        assert_eq!(
+            error.to_string(),
+            "line 1: bound \"99999999999999999999\" is out of range \
+             (number too large to fit in target type)"
+        )
+    }
+
+    #[test]
+    fn should_keep_reading_after_bad_lines() {
+        let input = "1-3 a: abcde\nnonsense\n2-9 c: ccccccccc\n5-2 b: b\n";
+        let (password_lines, errors) = extract_password_lines(input);
+        let lines: Vec<usize> = errors.iter().map(PasswordLineError::line).collect();
+
+        assert_eq!(password_lines.len(), 2);
+        assert_eq!(lines, vec![2, 4])
+    }
+
+    #[test]
+    fn should_read_punctuation_and_spaces() {
+        let line = PasswordLine::from_str("2-4 ab: p@ss w0rd!").unwrap();
+
+        assert_eq!(line.letter, "ab");
+        assert_eq!(line.password, "p@ss w0rd!")
+    }
+
+    #[test]
+    fn should_unquote_letter_and_password() {
+        let line = PasswordLine::from_str(r#"1-2 ": ": "say \"hi\": \\o/ ""#).unwrap();
+
+        assert_eq!(line.letter, ": ");
+        assert_eq!(line.password, r#"say "hi": \o/ "#);
+        assert_eq!(
+            PasswordLine::parse(3, r#"1-2 a: "open"#).unwrap_err(),
+            PasswordLineError::UnterminatedQuote { line: 3 }
+        )
+    }
+
+    #[test]
+    fn should_use_custom_separators() {
+        let grammar = Grammar::new("..", " | ", " => ").ignoring_case(true);
+        let line = grammar.parse(1, "1..3 | Ab => x y").unwrap();
+
+        assert_eq!((line.min, line.max), (1, 3));
+        assert_eq!((&*line.letter, &*line.password), ("Ab", "x y"));
+        assert!(line.ignore_case);
+        assert_eq!(
+            grammar.parse(2, "1-3 a: abc").unwrap_err().to_string(),
+            "line 2: \"1-3 a: abc\" does not look like `min..max | letter => password`"
+        )
+    }
+}
@@ -1,73 +1,224 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::env;
+use clap::{Arg, ArgAction, Command};
+use password_policy::{
+    audit, builtin, compare, count_valid, suggest, survey, tally_file, write_report, Grammar,
+    PasswordPolicy, ReportFormat, Rule, RulePolicy, Segmentation,
+};
 use std::fs;
-use std::str::FromStr;
+use std::io::{self, BufWriter};
+use std::process;
 
-#[derive(Debug)]
-struct PasswordLine {
-    min: usize,
-    max: usize,
-    letter: char,
-    password: String,
-}
+fn main() {
+    let matches = Command::new("AOC Day 02")
+        .about("Counts the passwords complying with their policy")
+        .arg(Arg::new("file").required(true).help("password database"))
+        .arg(
+            Arg::new("policy")
+                .short('p')
+                .long("policy")
+                .value_parser(|name: &str| {
+                    builtin(name, Segmentation::default()).map(|policy| policy.name().to_string())
+                })
+                .default_value("toboggan")
+                .help("policy the passwords are checked against: sled or toboggan"),
+        )
+        .arg(
+            Arg::new("rules")
+                .short('r')
+                .long("rules")
+                .conflicts_with("policy")
+                .help("file describing the policy as a rule, e.g. `count(letter, min, max)`"),
+        )
+        .arg(
+            Arg::new("segmentation")
+                .long("segmentation")
+                .value_parser(|name: &str| name.parse::<Segmentation>())
+                .default_value("scalar")
+                .help("what counts as one character: scalar (Unicode scalar value) or grapheme"),
+        )
+        .arg(
+            Arg::new("range-separator")
+                .long("range-separator")
+                .default_value("-")
+                .help("text between min and max"),
+        )
+        .arg(
+            Arg::new("letter-separator")
+                .long("letter-separator")
+                .default_value(" ")
+                .help("text between max and letter"),
+        )
+        .arg(
+            Arg::new("password-separator")
+                .long("password-separator")
+                .default_value(": ")
+                .help("text between letter and password"),
+        )
+        .arg(
+            Arg::new("ignore-case")
+                .short('i')
+                .long("ignore-case")
+                .action(ArgAction::SetTrue)
+                .help("match letters regardless of case"),
+        )
+        .arg(
+            Arg::new("compare")
+                .short('c')
+                .long("compare")
+                .value_parser(|name: &str| {
+                    builtin(name, Segmentation::default()).map(|policy| policy.name().to_string())
+                })
+                .conflicts_with_all(["format", "repair"])
+                .help("prints which verdicts change when migrating from this policy to the checked one"),
+        )
+        .arg(
+            Arg::new("repair")
+                .long("repair")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("format")
+                .help("suggests the smallest edits making failing passwords comply"),
+        )
+        .arg(
+            Arg::new("mmap")
+                .long("mmap")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["format", "compare", "repair", "stats", "weak"])
+                .help("counts a memory-mapped database in parallel, for huge dumps"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["format", "compare", "repair"])
+                .help("prints length, character class, letter and strength statistics, and each line's strength"),
+        )
+        .arg(
+            Arg::new("weak")
+                .long("weak")
+                .value_name("BITS")
+                .value_parser(clap::value_parser!(f64))
+                .conflicts_with_all(["format", "compare", "repair"])
+                .help("lists complying passwords with an estimated strength below BITS"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_parser(|name: &str| name.parse::<ReportFormat>())
+                .default_value("count")
+                .help("count of valid lines, or a per-line audit as jsonl or csv"),
+        )
+        .get_matches();
 
-lazy_static! {
-    static ref PASSWORD_LINE_RE: Regex =
-        Regex::new(r"^(?P<min>\d+)-(?P<max>\d+) (?P<letter>\w): (?P<password>\w+)$").unwrap();
-}
+    let input_file: &String = matches
+        .get_one::<String>("file")
+        .expect("'file' is required");
+    let segmentation: Segmentation = *matches
+        .get_one::<Segmentation>("segmentation")
+        .expect("'segmentation' has a default");
+    let policy: Box<dyn PasswordPolicy> = match matches.get_one::<String>("rules") {
+        Some(rules_file) => Box::new(load_rules(rules_file, segmentation)),
+        None => builtin(
+            matches
+                .get_one::<String>("policy")
+                .expect("'policy' has a default"),
+            segmentation,
+        )
+        .expect("policy names are validated by the parser"),
+    };
 
-impl PasswordLine {
-    fn evaluate_rule(&self) -> bool {
-        // let count = self.password.matches(self.letter).count();
-        let count = self.password
-            .match_indices(self.letter)
-            .filter(|item| item.0+1 == self.min || item.0+1 == self.max)
-            .count();
+    let format: ReportFormat = *matches
+        .get_one::<ReportFormat>("format")
+        .expect("'format' has a default");
 
-        count == 1
-    }
+    let separator = |name: &str| {
+        matches
+            .get_one::<String>(name)
+            .expect("separators have defaults")
+            .as_str()
+    };
+    let grammar = Grammar::new(
+        separator("range-separator"),
+        separator("letter-separator"),
+        separator("password-separator"),
+    )
+    .ignoring_case(matches.get_flag("ignore-case"));
 
-    fn is_valid(&self) -> bool {
-        self.evaluate_rule()
+    if matches.get_flag("mmap") {
+        let tally =
+            tally_file(input_file, &grammar, policy.as_ref()).expect("Could not read from file!");
+        for error in &tally.errors {
+            eprintln!("skipped {}", error);
+        }
+        println!("{}", tally.valid);
+        return;
     }
-}
-
-impl FromStr for PasswordLine {
-    type Err = std::string::ParseError;
-
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let c = PASSWORD_LINE_RE.captures(text).unwrap();
-
-        let min = u8::from_str(c.name("min").unwrap().as_str()).unwrap();
-        let max = u8::from_str(c.name("max").unwrap().as_str()).unwrap();
-        let letter = c.name("letter").unwrap().as_str();
-        let password = c.name("password").unwrap().as_str();
 
-        Ok(PasswordLine {
-            min: min as usize,
-            max: max as usize,
-            letter: char::from_str(letter).unwrap(),
-            password: password.into(),
-        })
+    let input = fs::read_to_string(input_file).expect("Could not read from file!");
+    if let Some(name) = matches.get_one::<String>("compare") {
+        let before = builtin(name, segmentation).expect("policy names are validated by the parser");
+        let comparison = compare(&input, &grammar, before.as_ref(), policy.as_ref());
+        for error in &comparison.skipped {
+            eprintln!("skipped {}", error);
+        }
+        print!("{}", comparison);
+        return;
+    }
+    let weak_bits = matches.get_one::<f64>("weak").copied();
+    if matches.get_flag("stats") || weak_bits.is_some() {
+        let (corpus, ratings, errors) = survey(&input, &grammar, policy.as_ref());
+        for error in &errors {
+            eprintln!("skipped {}", error);
+        }
+        if matches.get_flag("stats") {
+            print!("{}", corpus);
+            for rating in &ratings {
+                println!("strength {}", rating);
+            }
+        }
+        if let Some(min_bits) = weak_bits {
+            for rating in ratings.iter().filter(|rating| rating.is_weak(min_bits)) {
+                println!("weak {}", rating);
+            }
+        }
+        return;
+    }
+    if matches.get_flag("repair") {
+        let (suggestions, errors) = suggest(&input, &grammar, policy.as_ref());
+        for error in &errors {
+            eprintln!("skipped {}", error);
+        }
+        for suggestion in &suggestions {
+            println!("{}", suggestion);
+        }
+        return;
+    }
+    if format != ReportFormat::Count {
+        let entries = audit(&input, &grammar, policy.as_ref());
+        let mut out = BufWriter::new(io::stdout().lock());
+        write_report(&mut out, format, &entries).expect("Could not write report!");
+        return;
     }
-}
 
-fn main() {
-    let input_file: String = read_input_file();
-    let password_lines: Vec<PasswordLine> = extract_password_lines(input_file)
-        .into_iter().filter(PasswordLine::is_valid).collect();
+    let (password_lines, errors) = grammar.extract(&input);
+    for error in &errors {
+        eprintln!("skipped {}", error);
+    }
 
-    println!("{}", password_lines.len())
+    println!("{}", count_valid(policy.as_ref(), &password_lines))
 }
 
-fn read_input_file() -> String {
-    let args: Vec<String> = env::args().collect();
-    let input = args.get(1).expect("Missing commandline argument!");
+fn load_rules(rules_file: &str, segmentation: Segmentation) -> RulePolicy {
+    let text = fs::read_to_string(rules_file).expect("Could not read rules file!");
 
-    fs::read_to_string(input).expect("Could not read from file!")
+    match text.parse::<Rule>() {
+        Ok(rule) => RulePolicy {
+            name: rules_file.into(),
+            rule,
+            segmentation,
+        },
+        Err(error) => {
+            eprintln!("{}: {}", rules_file, error);
+            process::exit(1);
+        }
+    }
 }
-
-fn extract_password_lines(input: String) -> Vec<PasswordLine> {
-    input.lines().into_iter().map(PasswordLine::from_str).map(Result::unwrap).collect()
-}
\ No newline at end of file
@@ -0,0 +1,183 @@
+use crate::line::{Grammar, PasswordLineError};
+use crate::policy::PasswordPolicy;
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::{self, Utf8Error};
+
+/// Bytes of the database validated by one task, the last line of a chunk is completed.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Outcome of validating a whole database without keeping its lines.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Tally {
+    pub lines: usize,
+    pub valid: usize,
+    pub errors: Vec<PasswordLineError>,
+}
+
+impl Tally {
+    /// Appends the tally of the lines following `self`'s.
+    fn then(mut self, mut next: Tally) -> Tally {
+        for error in next.errors.iter_mut() {
+            error.shift(self.lines);
+        }
+        self.lines += next.lines;
+        self.valid += next.valid;
+        self.errors.append(&mut next.errors);
+        self
+    }
+}
+
+/// Splits `bytes` after the first line break following every `size` bytes.
+fn chunks(bytes: &[u8], size: usize) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut rest = bytes;
+
+    while rest.len() > size {
+        let end = match rest[size..].iter().position(|&byte| byte == b'\n') {
+            Some(offset) => size + offset + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+fn tally_chunk(
+    chunk: &[u8],
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> Result<Tally, Utf8Error> {
+    let mut tally = Tally::default();
+
+    for text in str::from_utf8(chunk)?.lines() {
+        tally.lines += 1;
+        match grammar.scan(tally.lines, text) {
+            Ok(line) => tally.valid += usize::from(policy.is_valid(&line)),
+            Err(error) => tally.errors.push(error),
+        }
+    }
+    Ok(tally)
+}
+
+fn tally_chunks(
+    bytes: &[u8],
+    size: usize,
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> Result<Tally, Utf8Error> {
+    let tallies = chunks(bytes, size)
+        .into_par_iter()
+        .map(|chunk| tally_chunk(chunk, grammar, policy))
+        .collect::<Result<Vec<Tally>, Utf8Error>>()?;
+
+    Ok(tallies.into_iter().fold(Tally::default(), Tally::then))
+}
+
+/// Counts the lines of `bytes` complying with `policy`, scanning chunks of lines on all threads
+/// of the rayon pool without copying them.
+pub fn tally(
+    bytes: &[u8],
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> Result<Tally, Utf8Error> {
+    tally_chunks(bytes, CHUNK_SIZE, grammar, policy)
+}
+
+/// Memory-maps the database at `path` and [`tally`]s it.
+pub fn tally_file(
+    path: impl AsRef<Path>,
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> io::Result<Tally> {
+    let file = File::open(path)?;
+    // SAFETY: the map is only read, and the database must not be modified while it is validated.
+    let map = unsafe { Mmap::map(&file)? };
+
+    tally(&map, grammar, policy).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::WITH_UNREADABLE_LINE;
+    use crate::line::{extract_password_lines, Grammar};
+    use crate::mapped::{chunks, tally, tally_chunks, Tally};
+    use crate::policy::{count_valid, Sled, Toboggan};
+
+    #[test]
+    fn should_split_after_line_breaks() {
+        let chunks = chunks(b"1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc", 4);
+
+        assert_eq!(
+            chunks,
+            vec![
+                &b"1-3 a: abcde\n"[..],
+                &b"1-3 b: cdefg\n"[..],
+                &b"2-9 c: ccccccccc"[..]
+            ]
+        )
+    }
+
+    #[test]
+    fn should_agree_with_regex_path() {
+        let input = format!("{}\"x\"\n1-2 \"a\": \"a\\\"b\"\n", WITH_UNREADABLE_LINE).repeat(50);
+        let (lines, errors) = extract_password_lines(&input);
+
+        for size in [1, 7, 100, 1 << 20] {
+            let tally = tally_chunks(
+                input.as_bytes(),
+                size,
+                &Grammar::default(),
+                &Sled::default(),
+            )
+            .unwrap();
+
+            assert_eq!(tally.lines, 300);
+            assert_eq!(tally.valid, count_valid(&Sled::default(), &lines));
+            assert_eq!(tally.errors, errors)
+        }
+    }
+
+    #[test]
+    fn should_scan_like_regex() {
+        let grammar = Grammar::new("..", " | ", " => ");
+        let texts = [
+            "1..3 | Ab => x y",
+            "1..3 | a | b => c => d",
+            "1..3 | \"=> \" => \"x\"",
+            "1..3 | \"a => b",
+            "1..3 |  => x",
+            "3..1 | a => x",
+            "1..99999999999999999999 | a => x",
+            "1-3 a: abc",
+            "1..3 | a => ",
+            "..3 | a => b",
+        ];
+
+        for (index, text) in texts.iter().enumerate() {
+            assert_eq!(
+                grammar.scan(index, text),
+                grammar.parse(index, text),
+                "{}",
+                text
+            )
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_utf8() {
+        assert!(tally(b"1-3 a: \xff", &Grammar::default(), &Toboggan::default()).is_err());
+        assert_eq!(
+            tally(b"", &Grammar::default(), &Toboggan::default()).unwrap(),
+            Tally::default()
+        )
+    }
+}
@@ -0,0 +1,303 @@
+use crate::line::PasswordLine;
+use crate::repair::{self, Repair};
+use crate::segment::Segmentation;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error;
+
+/// Rule deciding whether the password of a line complies with the line's policy.
+///
+/// Implement it to check a database against policies other than the built-in ones. Policies are
+/// shared between the threads validating a database in parallel.
+pub trait PasswordPolicy: Sync {
+    fn name(&self) -> &str;
+
+    fn is_valid(&self, line: &PasswordLine) -> bool;
+
+    /// Explains the verdict on `line`, policies without details only tell whether it is valid.
+    fn audit(&self, line: &PasswordLine) -> Verdict {
+        let valid = self.is_valid(line);
+
+        Verdict {
+            valid,
+            observed: None,
+            reason: if valid {
+                None
+            } else {
+                Some(format!("rejected by {}", self.name()))
+            },
+        }
+    }
+
+    /// The smallest change to the password found that complies with the policy, `None` for
+    /// policies that do not know how to repair passwords.
+    fn repair(&self, _line: &PasswordLine) -> Option<Repair> {
+        None
+    }
+}
+
+/// What a policy found in a password.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Observation {
+    /// Occurrences of the letter.
+    Count(usize),
+    /// Checked 1-based positions holding the letter.
+    Positions(Vec<usize>),
+}
+
+impl Display for Observation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Observation::Count(count) => write!(f, "count {}", count),
+            Observation::Positions(positions) => {
+                f.write_str("positions")?;
+                for position in positions {
+                    write!(f, " {}", position)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Verdict {
+    pub valid: bool,
+    pub observed: Option<Observation>,
+    /// Why the password fails, `None` for valid passwords.
+    pub reason: Option<String>,
+}
+
+/// The sled rental's policy: `letter` occurs at least `min` and at most `max` times.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Sled {
+    pub segmentation: Segmentation,
+}
+
+impl Sled {
+    pub fn new(segmentation: Segmentation) -> Self {
+        Sled { segmentation }
+    }
+}
+
+impl PasswordPolicy for Sled {
+    fn name(&self) -> &str {
+        "sled"
+    }
+
+    fn is_valid(&self, line: &PasswordLine) -> bool {
+        let count = line.occurrences(self.segmentation).len();
+        count >= line.min && count <= line.max
+    }
+
+    fn audit(&self, line: &PasswordLine) -> Verdict {
+        let count = line.occurrences(self.segmentation).len();
+        let valid = count >= line.min && count <= line.max;
+
+        Verdict {
+            valid,
+            observed: Some(Observation::Count(count)),
+            reason: if valid {
+                None
+            } else {
+                Some(format!(
+                    "'{}' occurs {} times, expected {} to {}",
+                    line.letter, count, line.min, line.max
+                ))
+            },
+        }
+    }
+
+    fn repair(&self, line: &PasswordLine) -> Option<Repair> {
+        repair::sled(self, line, self.segmentation)
+    }
+}
+
+/// The toboggan corporate policy: `letter` is at exactly one of the 1-based positions `min` and
+/// `max`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Toboggan {
+    pub segmentation: Segmentation,
+}
+
+impl Toboggan {
+    pub fn new(segmentation: Segmentation) -> Self {
+        Toboggan { segmentation }
+    }
+}
+
+impl PasswordPolicy for Toboggan {
+    fn name(&self) -> &str {
+        "toboggan"
+    }
+
+    fn is_valid(&self, line: &PasswordLine) -> bool {
+        let at_min = line.letter_at(self.segmentation, line.min);
+        at_min != (line.max != line.min && line.letter_at(self.segmentation, line.max))
+    }
+
+    fn audit(&self, line: &PasswordLine) -> Verdict {
+        let mut positions = vec![line.min];
+        if line.max != line.min {
+            positions.push(line.max);
+        }
+        positions.retain(|position| line.letter_at(self.segmentation, *position));
+        let reason = match positions.len() {
+            1 => None,
+            0 => Some(format!(
+                "'{}' is at neither position {} nor {}",
+                line.letter, line.min, line.max
+            )),
+            _ => Some(format!(
+                "'{}' is at both positions {} and {}",
+                line.letter, line.min, line.max
+            )),
+        };
+
+        Verdict {
+            valid: reason.is_none(),
+            observed: Some(Observation::Positions(positions)),
+            reason,
+        }
+    }
+
+    fn repair(&self, line: &PasswordLine) -> Option<Repair> {
+        repair::toboggan(self, line, self.segmentation)
+    }
+}
+
+pub const BUILTIN_POLICIES: [&str; 2] = ["sled", "toboggan"];
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("unknown policy {0:?}, expected one of {}", BUILTIN_POLICIES.join(", "))]
+pub struct UnknownPolicy(pub String);
+
+/// Looks up a built-in policy by name, reading passwords with `segmentation`.
+pub fn builtin(
+    name: &str,
+    segmentation: Segmentation,
+) -> Result<Box<dyn PasswordPolicy>, UnknownPolicy> {
+    match name {
+        "sled" => Ok(Box::new(Sled::new(segmentation))),
+        "toboggan" => Ok(Box::new(Toboggan::new(segmentation))),
+        _ => Err(UnknownPolicy(name.into())),
+    }
+}
+
+pub fn count_valid<'a, 'b: 'a>(
+    policy: &dyn PasswordPolicy,
+    lines: impl IntoIterator<Item = &'a PasswordLine<'b>>,
+) -> usize {
+    lines
+        .into_iter()
+        .filter(|line| policy.is_valid(line))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::AOC_EXAMPLE;
+    use crate::line::{extract_password_lines, Grammar, PasswordLine};
+    use crate::policy::{
+        builtin, count_valid, Observation, PasswordPolicy, Sled, Toboggan, BUILTIN_POLICIES,
+    };
+    use crate::segment::Segmentation;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_count_aoc_example() {
+        let (lines, _) = extract_password_lines(AOC_EXAMPLE);
+
+        assert_eq!(count_valid(&Sled::default(), &lines), 2);
+        assert_eq!(count_valid(&Toboggan::default(), &lines), 1)
+    }
+
+    #[test]
+    fn should_count_positions_in_characters_not_bytes() {
+        let line = PasswordLine::from_str("1-3 a: äba").unwrap();
+        let shifted = PasswordLine::from_str("2-4 a: äba").unwrap();
+
+        assert!(Toboggan::default().is_valid(&line));
+        assert!(!Toboggan::default().is_valid(&shifted))
+    }
+
+    #[test]
+    fn should_count_letters_of_non_ascii_passwords() {
+        let line = PasswordLine::from_str("2-2 ü: müsliüß").unwrap();
+
+        assert!(Sled::default().is_valid(&line));
+        assert!(Sled::new(Segmentation::Grapheme).is_valid(&line))
+    }
+
+    #[test]
+    fn should_treat_combined_characters_as_one_grapheme() {
+        let position = PasswordLine::from_str("1-2 x: e\u{301}x").unwrap();
+        let count = PasswordLine::from_str("1-1 e: e\u{301}").unwrap();
+
+        assert!(!Toboggan::new(Segmentation::Scalar).is_valid(&position));
+        assert!(Toboggan::new(Segmentation::Grapheme).is_valid(&position));
+        assert!(Sled::new(Segmentation::Scalar).is_valid(&count));
+        assert!(!Sled::new(Segmentation::Grapheme).is_valid(&count))
+    }
+
+    #[test]
+    fn should_match_substrings_regardless_of_case() {
+        let grammar = Grammar::default().ignoring_case(true);
+        let line = grammar.parse(1, "2-3 ab: AbxaBab").unwrap();
+        let exact = PasswordLine::from_str("2-3 ab: AbxaBab").unwrap();
+
+        assert!(Sled::default().is_valid(&line));
+        assert!(!Sled::default().is_valid(&exact));
+        assert_eq!(
+            Toboggan::default().audit(&line).observed,
+            Some(Observation::Positions(vec![]))
+        );
+        assert!(Toboggan::default().is_valid(&grammar.parse(2, "1-3 ab: ABxab").unwrap()))
+    }
+
+    #[test]
+    fn should_explain_verdicts() {
+        let (lines, _) = extract_password_lines(AOC_EXAMPLE);
+        let sled = Sled::default().audit(&lines[1]);
+        let toboggan = Toboggan::default().audit(&lines[2]);
+
+        assert_eq!(sled.observed, Some(Observation::Count(0)));
+        assert_eq!(sled.reason.unwrap(), "'b' occurs 0 times, expected 1 to 3");
+        assert_eq!(toboggan.observed, Some(Observation::Positions(vec![2, 9])));
+        assert_eq!(toboggan.reason.unwrap(), "'c' is at both positions 2 and 9");
+        assert_eq!(Toboggan::default().audit(&lines[0]).reason, None)
+    }
+
+    #[test]
+    fn should_look_up_builtin_policies() {
+        for name in BUILTIN_POLICIES.iter() {
+            assert_eq!(builtin(name, Segmentation::Scalar).unwrap().name(), *name)
+        }
+        assert_eq!(
+            builtin("sleigh", Segmentation::Scalar)
+                .err()
+                .map(|error| error.to_string()),
+            Some("unknown policy \"sleigh\", expected one of sled, toboggan".into())
+        )
+    }
+
+    #[test]
+    fn should_accept_third_party_policies() {
+        struct Longer(usize);
+
+        impl PasswordPolicy for Longer {
+            fn name(&self) -> &str {
+                "longer"
+            }
+
+            fn is_valid(&self, line: &PasswordLine) -> bool {
+                line.password.len() > self.0
+            }
+        }
+
+        let (lines, _) = extract_password_lines(AOC_EXAMPLE);
+
+        assert_eq!(count_valid(&Longer(5), &lines), 1)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::line::{Grammar, PasswordLine, PasswordLineError};
+use crate::policy::PasswordPolicy;
+use crate::segment::Segmentation;
+use std::fmt::{self, Display, Formatter};
+
+/// A password changed to comply with a policy.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Repair {
+    pub password: String,
+    /// Characters inserted, deleted or substituted to get from the old password to the new one.
+    pub distance: usize,
+}
+
+/// Characters written where a letter has to go away or a password has to get longer.
+const FILLERS: [&str; 2] = ["_", "-"];
+
+/// Levenshtein distance between `from` and `to`, counted in characters of `segmentation`.
+pub fn edit_distance(from: &str, to: &str, segmentation: Segmentation) -> usize {
+    let from: Vec<&str> = segmentation.units(from).collect();
+    let to: Vec<&str> = segmentation.units(to).collect();
+    let mut previous: Vec<usize> = (0..=to.len()).collect();
+
+    for (i, unit) in from.iter().enumerate() {
+        let mut current = vec![i + 1; to.len() + 1];
+        for (j, other) in to.iter().enumerate() {
+            let substitution = previous[j] + usize::from(unit != other);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[to.len()]
+}
+
+/// Keeps `units` as the repair of `line` if `policy` accepts it.
+fn checked(
+    policy: &dyn PasswordPolicy,
+    line: &PasswordLine,
+    units: Vec<String>,
+    segmentation: Segmentation,
+) -> Option<Repair> {
+    let repaired = PasswordLine {
+        password: units.concat().into(),
+        ..line.clone()
+    };
+    if repaired.password.is_empty() || !policy.is_valid(&repaired) {
+        return None;
+    }
+
+    Some(Repair {
+        distance: edit_distance(&line.password, &repaired.password, segmentation),
+        password: repaired.password.into_owned(),
+    })
+}
+
+fn unchanged(line: &PasswordLine) -> Option<Repair> {
+    Some(Repair {
+        password: line.password.to_string(),
+        distance: 0,
+    })
+}
+
+fn units(text: &str, segmentation: Segmentation) -> Vec<String> {
+    segmentation.units(text).map(String::from).collect()
+}
+
+/// A character that is not the start of the line's letter.
+fn filler(line: &PasswordLine, segmentation: Segmentation) -> String {
+    FILLERS
+        .iter()
+        .find(|filler| !segmentation.letter_at(filler, &line.letter, 1, line.ignore_case))
+        .expect("a letter starts with at most one of the fillers")
+        .to_string()
+}
+
+/// Brings the number of occurrences of the letter within bounds, substituting the letter for other
+/// characters or a filler for one character of each extra occurrence, trying each character of
+/// the letter until the policy accepts the result. Minimal when the letter is a single character.
+pub(crate) fn sled(
+    policy: &dyn PasswordPolicy,
+    line: &PasswordLine,
+    segmentation: Segmentation,
+) -> Option<Repair> {
+    if policy.is_valid(line) {
+        return unchanged(line);
+    }
+    let occurrences = line.occurrences(segmentation);
+    let letter = units(&line.letter, segmentation);
+    let mut password = units(&line.password, segmentation);
+
+    if occurrences.len() < line.min {
+        let mut missing = line.min - occurrences.len();
+        if letter.len() == 1 {
+            for position in 1..=password.len() {
+                if missing > 0 && !line.letter_at(segmentation, position) {
+                    password[position - 1] = letter[0].clone();
+                    missing -= 1;
+                }
+            }
+        }
+        for _ in 0..missing {
+            password.extend(letter.iter().cloned());
+        }
+    } else if occurrences.len() > line.max {
+        let filler = filler(line, segmentation);
+        return (0..letter.len()).find_map(|offset| {
+            let mut password = password.clone();
+            for position in occurrences.iter().skip(line.max) {
+                password[position - 1 + offset] = filler.clone();
+            }
+            checked(policy, line, password, segmentation)
+        });
+    }
+
+    checked(policy, line, password, segmentation)
+}
+
+/// Puts the letter at exactly one of the two positions, substituting one character, or padding
+/// passwords too short to have the first position. A first position of 0 is never taken, so the
+/// letter goes to the second one then. Minimal when the letter is a single character.
+pub(crate) fn toboggan(
+    policy: &dyn PasswordPolicy,
+    line: &PasswordLine,
+    segmentation: Segmentation,
+) -> Option<Repair> {
+    if policy.is_valid(line) {
+        return unchanged(line);
+    }
+    let letter = units(&line.letter, segmentation);
+    let mut password = units(&line.password, segmentation);
+
+    if line.letter_at(segmentation, line.min) {
+        let last = line.max - 1 + letter.len() - 1;
+        *password.get_mut(last)? = filler(line, segmentation);
+    } else {
+        let position = if line.min > 0 { line.min } else { line.max };
+        let start = position.checked_sub(1)?;
+        while password.len() < start {
+            password.push(filler(line, segmentation));
+        }
+        for (offset, unit) in letter.iter().enumerate() {
+            match password.get_mut(start + offset) {
+                Some(old) => *old = unit.clone(),
+                None => password.push(unit.clone()),
+            }
+        }
+    }
+
+    checked(policy, line, password, segmentation)
+}
+
+/// The smallest change found to a line failing its policy.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Suggestion {
+    pub line: usize,
+    pub password: String,
+    /// `None` if the policy does not know how to repair the password.
+    pub repair: Option<Repair>,
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.repair {
+            Some(repair) => write!(
+                f,
+                "line {}: {} -> {} ({} edit{})",
+                self.line,
+                self.password,
+                repair.password,
+                repair.distance,
+                if repair.distance == 1 { "" } else { "s" }
+            ),
+            None => write!(f, "line {}: {} has no repair", self.line, self.password),
+        }
+    }
+}
+
+/// Suggests repairs for the lines of `input` failing `policy`, collecting the unreadable lines.
+pub fn suggest(
+    input: &str,
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> (Vec<Suggestion>, Vec<PasswordLineError>) {
+    let mut suggestions = Vec::new();
+    let mut errors = Vec::new();
+
+    for (number, parsed) in grammar.lines(input) {
+        match parsed {
+            Ok(line) if !policy.is_valid(&line) => suggestions.push(Suggestion {
+                line: number,
+                repair: policy.repair(&line),
+                password: line.password.into_owned(),
+            }),
+            Ok(_) => {}
+            Err(error) => errors.push(error),
+        }
+    }
+
+    (suggestions, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::WITH_UNREADABLE_LINE;
+    use crate::line::{Grammar, PasswordLine};
+    use crate::policy::{PasswordPolicy, Sled, Toboggan};
+    use crate::repair::{edit_distance, suggest, Repair};
+    use crate::segment::Segmentation;
+    use std::str::FromStr;
+
+    fn repair(policy: &dyn PasswordPolicy, text: &str) -> Option<Repair> {
+        policy.repair(&PasswordLine::from_str(text).unwrap())
+    }
+
+    fn repaired(password: &str, distance: usize) -> Option<Repair> {
+        Some(Repair {
+            password: password.into(),
+            distance,
+        })
+    }
+
+    #[test]
+    fn should_measure_edit_distance() {
+        let scalar = Segmentation::Scalar;
+
+        assert_eq!(edit_distance("kitten", "sitting", scalar), 3);
+        assert_eq!(edit_distance("", "abc", scalar), 3);
+        assert_eq!(edit_distance("e\u{301}x", "x", scalar), 2);
+        assert_eq!(edit_distance("e\u{301}x", "x", Segmentation::Grapheme), 1)
+    }
+
+    #[test]
+    fn should_add_missing_letters() {
+        assert_eq!(
+            repair(&Sled::default(), "1-3 b: cdefg"),
+            repaired("bdefg", 1)
+        );
+        assert_eq!(repair(&Sled::default(), "3-4 a: ab"), repaired("aaa", 2));
+        assert_eq!(repair(&Sled::default(), "2-2 ab: ab"), repaired("abab", 2))
+    }
+
+    #[test]
+    fn should_remove_extra_letters() {
+        assert_eq!(
+            repair(&Sled::default(), "1-2 c: ccxcc"),
+            repaired("ccx__", 2)
+        );
+        assert_eq!(repair(&Sled::default(), "0-0 c: cc"), repaired("__", 2));
+        assert_eq!(
+            repair(&Sled::default(), "0-0 ab: aabb"),
+            repaired("a_bb", 1)
+        );
+        assert_eq!(
+            repair(&Sled::default(), "0-0 aba: ababa"),
+            repaired("ab_ba", 1)
+        )
+    }
+
+    #[test]
+    fn should_move_letter_to_one_position() {
+        let toboggan = Toboggan::default();
+
+        assert_eq!(
+            repair(&toboggan, "2-9 c: ccccccccc"),
+            repaired("cccccccc_", 1)
+        );
+        assert_eq!(repair(&toboggan, "1-3 b: cdefg"), repaired("bdefg", 1));
+        assert_eq!(repair(&toboggan, "4-6 b: xy"), repaired("xy_b", 2));
+        assert_eq!(repair(&toboggan, "1-2 aa: aaa"), repaired("aa_", 1));
+        assert_eq!(repair(&toboggan, "0-3 b: xxxx"), repaired("xxbx", 1));
+        assert_eq!(repair(&toboggan, "0-0 b: xxxx"), None);
+        assert_eq!(repair(&toboggan, "1-3 a: abcde"), repaired("abcde", 0))
+    }
+
+    #[test]
+    fn should_suggest_repairs_for_failing_lines() {
+        let (suggestions, errors) = suggest(
+            WITH_UNREADABLE_LINE,
+            &Grammar::default(),
+            &Toboggan::default(),
+        );
+        let lines: Vec<String> = suggestions.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "line 2: cdefg -> bdefg (1 edit)",
+                "line 4: ccccccccc -> cccccccc_ (1 edit)"
+            ]
+        );
+        assert_eq!(errors.len(), 1)
+    }
+}
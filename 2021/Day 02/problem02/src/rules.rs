@@ -0,0 +1,610 @@
+//! Password rules described in a small expression language instead of code.
+//!
+//! ```text
+//! # the toboggan policy, but passwords also need eight characters
+//! exactly_one(letter, min, max) and min_length(8)
+//!     and not matches("^[0-9]+$")
+//! ```
+//!
+//! Rules combine with `and`, `or`, `not` and parentheses, `not` binding tightest and `or`
+//! loosest. The checks are
+//!
+//! - `count(L, A, B)`: letter `L` occurs at least `A` and at most `B` times,
+//! - `exactly_one(L, P, ...)`: letter `L` is at exactly one of the 1-based positions `P, ...`,
+//! - `matches("regex")`: the password matches the regular expression,
+//! - `min_length(N)`: the password has at least `N` characters.
+//!
+//! Letters are `letter`, the letter of the line, or a literal like `'x'`. Numbers are `min`,
+//! `max` or a literal like `3`. Everything after a `#` up to the end of the line is a comment.
+
+use crate::line::PasswordLine;
+use crate::policy::PasswordPolicy;
+use crate::segment::Segmentation;
+use regex::Regex;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A parsed rule, evaluated against one password line at a time.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    Count {
+        letter: Letter,
+        min: Number,
+        max: Number,
+    },
+    ExactlyOne {
+        letter: Letter,
+        positions: Vec<Number>,
+    },
+    Matches(Regex),
+    MinLength(Number),
+    Not(Box<Rule>),
+    And(Box<Rule>, Box<Rule>),
+    Or(Box<Rule>, Box<Rule>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Letter {
+    OfLine,
+    Literal(char),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Number {
+    Min,
+    Max,
+    Literal(usize),
+}
+
+impl Letter {
+    fn of(&self, line: &PasswordLine) -> String {
+        match self {
+            Letter::OfLine => line.letter.to_string(),
+            Letter::Literal(letter) => letter.to_string(),
+        }
+    }
+}
+
+impl Number {
+    fn of(&self, line: &PasswordLine) -> usize {
+        match self {
+            Number::Min => line.min,
+            Number::Max => line.max,
+            Number::Literal(number) => *number,
+        }
+    }
+}
+
+impl Rule {
+    pub fn evaluate(&self, line: &PasswordLine, segmentation: Segmentation) -> bool {
+        match self {
+            Rule::Count { letter, min, max } => {
+                let letter = letter.of(line);
+                let count = segmentation
+                    .occurrences(&line.password, &letter, line.ignore_case)
+                    .len();
+
+                count >= min.of(line) && count <= max.of(line)
+            }
+            Rule::ExactlyOne { letter, positions } => {
+                let letter = letter.of(line);
+                let mut positions: Vec<usize> =
+                    positions.iter().map(|position| position.of(line)).collect();
+                positions.sort_unstable();
+                positions.dedup();
+                let count = positions
+                    .iter()
+                    .filter(|position| {
+                        segmentation.letter_at(
+                            &line.password,
+                            &letter,
+                            **position,
+                            line.ignore_case,
+                        )
+                    })
+                    .count();
+
+                count == 1
+            }
+            Rule::Matches(regex) => regex.is_match(&line.password),
+            Rule::MinLength(length) => {
+                segmentation.units(&line.password).count() >= length.of(line)
+            }
+            Rule::Not(rule) => !rule.evaluate(line, segmentation),
+            Rule::And(left, right) => {
+                left.evaluate(line, segmentation) && right.evaluate(line, segmentation)
+            }
+            Rule::Or(left, right) => {
+                left.evaluate(line, segmentation) || right.evaluate(line, segmentation)
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+        };
+        let rule = parser.or()?;
+        match parser.peek() {
+            (Token::End, _) => Ok(rule),
+            (token, at) => Err(at.error(ErrorKind::Expected {
+                expected: "`and`, `or` or end of rule",
+                found: token.to_string(),
+            })),
+        }
+    }
+}
+
+/// A rule loaded from a file, usable wherever a built-in policy is.
+#[derive(Debug, Clone)]
+pub struct RulePolicy {
+    pub name: String,
+    pub rule: Rule,
+    pub segmentation: Segmentation,
+}
+
+impl PasswordPolicy for RulePolicy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_valid(&self, line: &PasswordLine) -> bool {
+        self.rule.evaluate(line, self.segmentation)
+    }
+}
+
+/// 1-based position of a character in a rule file.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    fn error(self, kind: ErrorKind) -> RuleError {
+        RuleError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("line {line}, column {column}: {kind}")]
+pub struct RuleError {
+    pub line: usize,
+    pub column: usize,
+    #[source]
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    #[error("unexpected character {0:?}")]
+    UnexpectedCharacter(char),
+    #[error("string is not terminated")]
+    UnterminatedString,
+    #[error("letter literal needs exactly one character between single quotes")]
+    BadLetter,
+    #[error("number {0:?} is too large")]
+    BadNumber(String),
+    #[error("expected {expected}, found {found}")]
+    Expected {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("unknown check `{0}`, expected count, exactly_one, matches or min_length")]
+    UnknownCheck(String),
+    #[error("`{check}` takes {expected}, found {found} arguments")]
+    Arguments {
+        check: &'static str,
+        expected: &'static str,
+        found: usize,
+    },
+    #[error("invalid regular expression: {0}")]
+    BadRegex(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Token {
+    Word(String),
+    Number(String),
+    Letter(char),
+    Text(String),
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Number(number) => write!(f, "`{}`", number),
+            Token::Letter(letter) => write!(f, "{:?}", letter),
+            Token::Text(text) => write!(f, "{:?}", text),
+            Token::Open => f.write_str("`(`"),
+            Token::Close => f.write_str("`)`"),
+            Token::Comma => f.write_str("`,`"),
+            Token::End => f.write_str("end of rule"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, Position)>, RuleError> {
+    let mut tokens = Vec::new();
+    let mut at = Position { line: 1, column: 1 };
+
+    for (line_index, line) in text.lines().enumerate() {
+        let mut chars = line.chars().enumerate().peekable();
+        while let Some((index, c)) = chars.next() {
+            at = Position {
+                line: line_index + 1,
+                column: index + 1,
+            };
+            let token = match c {
+                '#' => break,
+                c if c.is_whitespace() => continue,
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                '"' => {
+                    let mut text = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.next() {
+                                Some((_, c)) if c == '"' || c == '\\' => text.push(c),
+                                Some((_, c)) => text.extend(['\\', c].iter()),
+                                None => return Err(at.error(ErrorKind::UnterminatedString)),
+                            },
+                            Some((_, c)) => text.push(c),
+                            None => return Err(at.error(ErrorKind::UnterminatedString)),
+                        }
+                    }
+                    Token::Text(text)
+                }
+                '\'' => match (chars.next(), chars.next()) {
+                    (Some((_, letter)), Some((_, '\''))) if letter != '\'' => Token::Letter(letter),
+                    _ => return Err(at.error(ErrorKind::BadLetter)),
+                },
+                c if c.is_ascii_digit() => {
+                    let mut number = c.to_string();
+                    while let Some((_, c)) = chars.peek().filter(|(_, c)| c.is_ascii_digit()) {
+                        number.push(*c);
+                        chars.next();
+                    }
+                    Token::Number(number)
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some((_, c)) = chars
+                        .peek()
+                        .filter(|(_, c)| c.is_alphanumeric() || *c == '_')
+                    {
+                        word.push(*c);
+                        chars.next();
+                    }
+                    Token::Word(word)
+                }
+                c => return Err(at.error(ErrorKind::UnexpectedCharacter(c))),
+            };
+            tokens.push((token, at));
+        }
+        at = Position {
+            line: line_index + 1,
+            column: line.chars().count() + 1,
+        };
+    }
+
+    tokens.push((Token::End, at));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> (Token, Position) {
+        self.tokens[self.next].clone()
+    }
+
+    fn advance(&mut self) -> (Token, Position) {
+        let token = self.peek();
+        if token.0 != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(&self.tokens[self.next].0, Token::Word(found) if found == word)
+    }
+
+    fn expect(&mut self, expected: Token, description: &'static str) -> Result<(), RuleError> {
+        match self.advance() {
+            (token, _) if token == expected => Ok(()),
+            (token, at) => Err(at.error(ErrorKind::Expected {
+                expected: description,
+                found: token.to_string(),
+            })),
+        }
+    }
+
+    fn or(&mut self) -> Result<Rule, RuleError> {
+        let mut rule = self.and()?;
+        while self.is_word("or") {
+            self.advance();
+            rule = Rule::Or(Box::new(rule), Box::new(self.and()?));
+        }
+        Ok(rule)
+    }
+
+    fn and(&mut self) -> Result<Rule, RuleError> {
+        let mut rule = self.unary()?;
+        while self.is_word("and") {
+            self.advance();
+            rule = Rule::And(Box::new(rule), Box::new(self.unary()?));
+        }
+        Ok(rule)
+    }
+
+    fn unary(&mut self) -> Result<Rule, RuleError> {
+        if self.is_word("not") {
+            self.advance();
+            return Ok(Rule::Not(Box::new(self.unary()?)));
+        }
+
+        match self.advance() {
+            (Token::Open, _) => {
+                let rule = self.or()?;
+                self.expect(Token::Close, "`)`")?;
+                Ok(rule)
+            }
+            (Token::Word(name), at) => self.check(name, at),
+            (token, at) => Err(at.error(ErrorKind::Expected {
+                expected: "a check, `not` or `(`",
+                found: token.to_string(),
+            })),
+        }
+    }
+
+    fn check(&mut self, name: String, at: Position) -> Result<Rule, RuleError> {
+        let check: &'static str = match name.as_str() {
+            "count" => "count",
+            "exactly_one" => "exactly_one",
+            "matches" => "matches",
+            "min_length" => "min_length",
+            _ => return Err(at.error(ErrorKind::UnknownCheck(name))),
+        };
+
+        self.expect(Token::Open, "`(` after the check")?;
+        let mut arguments = Vec::new();
+        if self.peek().0 != Token::Close {
+            loop {
+                arguments.push(self.advance());
+                match self.advance() {
+                    (Token::Comma, _) => continue,
+                    (Token::Close, _) => break,
+                    (token, at) => {
+                        return Err(at.error(ErrorKind::Expected {
+                            expected: "`,` or `)`",
+                            found: token.to_string(),
+                        }))
+                    }
+                }
+            }
+        } else {
+            self.advance();
+        }
+
+        let count = |expected: &'static str, fits: bool| {
+            if fits {
+                Ok(())
+            } else {
+                Err(at.error(ErrorKind::Arguments {
+                    check,
+                    expected,
+                    found: arguments.len(),
+                }))
+            }
+        };
+
+        match check {
+            "count" => {
+                count("a letter and two numbers", arguments.len() == 3)?;
+                Ok(Rule::Count {
+                    letter: letter(&arguments[0])?,
+                    min: number(&arguments[1])?,
+                    max: number(&arguments[2])?,
+                })
+            }
+            "exactly_one" => {
+                count("a letter and at least one position", arguments.len() >= 2)?;
+                Ok(Rule::ExactlyOne {
+                    letter: letter(&arguments[0])?,
+                    positions: arguments[1..]
+                        .iter()
+                        .map(number)
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            "matches" => {
+                count("one regular expression", arguments.len() == 1)?;
+                match &arguments[0] {
+                    (Token::Text(pattern), at) => Regex::new(pattern)
+                        .map(Rule::Matches)
+                        .map_err(|error| at.error(ErrorKind::BadRegex(error.to_string()))),
+                    (token, at) => Err(at.error(ErrorKind::Expected {
+                        expected: "a quoted regular expression",
+                        found: token.to_string(),
+                    })),
+                }
+            }
+            _ => {
+                count("one number", arguments.len() == 1)?;
+                Ok(Rule::MinLength(number(&arguments[0])?))
+            }
+        }
+    }
+}
+
+fn letter((token, at): &(Token, Position)) -> Result<Letter, RuleError> {
+    match token {
+        Token::Word(word) if word == "letter" => Ok(Letter::OfLine),
+        Token::Letter(letter) => Ok(Letter::Literal(*letter)),
+        token => Err(at.error(ErrorKind::Expected {
+            expected: "`letter` or a quoted letter",
+            found: token.to_string(),
+        })),
+    }
+}
+
+fn number((token, at): &(Token, Position)) -> Result<Number, RuleError> {
+    match token {
+        Token::Word(word) if word == "min" => Ok(Number::Min),
+        Token::Word(word) if word == "max" => Ok(Number::Max),
+        Token::Number(number) => number
+            .parse()
+            .map(Number::Literal)
+            .map_err(|_| at.error(ErrorKind::BadNumber(number.clone()))),
+        token => Err(at.error(ErrorKind::Expected {
+            expected: "`min`, `max` or a number",
+            found: token.to_string(),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::AOC_EXAMPLE;
+    use crate::line::{extract_password_lines, PasswordLine};
+    use crate::policy::{count_valid, Sled, Toboggan};
+    use crate::rules::{ErrorKind, Rule, RuleError, RulePolicy};
+    use crate::segment::Segmentation;
+    use std::str::FromStr;
+
+    fn policy(text: &str) -> RulePolicy {
+        RulePolicy {
+            name: "test".into(),
+            rule: Rule::from_str(text).unwrap(),
+            segmentation: Segmentation::Scalar,
+        }
+    }
+
+    fn error(text: &str) -> RuleError {
+        Rule::from_str(text).unwrap_err()
+    }
+
+    #[test]
+    fn should_express_builtin_policies() {
+        let (lines, _) = extract_password_lines(AOC_EXAMPLE);
+
+        assert_eq!(
+            count_valid(&policy("count(letter, min, max)"), &lines),
+            count_valid(&Sled::default(), &lines)
+        );
+        assert_eq!(
+            count_valid(&policy("exactly_one(letter, min, max)"), &lines),
+            count_valid(&Toboggan::default(), &lines)
+        )
+    }
+
+    #[test]
+    fn should_combine_checks() {
+        let rule = policy(
+            "# longer toboggan passwords without digits only\n\
+             exactly_one(letter, min, max) and min_length(6)\n\
+             \tand not matches(\"^[0-9]+$\")",
+        );
+        let (lines, _) = extract_password_lines("1-3 a: abcdef\n1-3 a: abcde\n2-9 c: ccccccccc\n");
+        let verdicts: Vec<bool> = lines
+            .iter()
+            .map(|line| rule.rule.evaluate(line, rule.segmentation))
+            .collect();
+
+        assert_eq!(verdicts, vec![true, false, false])
+    }
+
+    #[test]
+    fn should_bind_and_tighter_than_or() {
+        let line = PasswordLine::from_str("1-3 a: xyz").unwrap();
+
+        assert!(policy("min_length(1) or min_length(9) and min_length(9)")
+            .rule
+            .evaluate(&line, Segmentation::Scalar));
+        assert!(
+            !policy("(min_length(1) or min_length(9)) and min_length(9)")
+                .rule
+                .evaluate(&line, Segmentation::Scalar)
+        )
+    }
+
+    #[test]
+    fn should_use_literals() {
+        let line = PasswordLine::from_str("1-3 a: xyzäx").unwrap();
+
+        assert!(policy("count('x', 2, 2) and exactly_one('ä', 1, 4, 9)")
+            .rule
+            .evaluate(&line, Segmentation::Scalar))
+    }
+
+    #[test]
+    fn should_point_at_bad_rules() {
+        assert_eq!(
+            error("count(letter, min max)"),
+            RuleError {
+                line: 1,
+                column: 19,
+                kind: ErrorKind::Expected {
+                    expected: "`,` or `)`",
+                    found: "`max`".into()
+                }
+            }
+        );
+        assert_eq!(
+            error("count(letter, 1, 2)\n  or lenght(3)").to_string(),
+            "line 2, column 6: unknown check `lenght`, expected count, exactly_one, matches or min_length"
+        );
+        assert_eq!(
+            error("exactly_one(letter)").kind,
+            ErrorKind::Arguments {
+                check: "exactly_one",
+                expected: "a letter and at least one position",
+                found: 1
+            }
+        );
+        assert_eq!(error("matches(\"[a-\")").column, 9);
+        assert_eq!(
+            error("min_length(8) and").kind,
+            ErrorKind::Expected {
+                expected: "a check, `not` or `(`",
+                found: "end of rule".into()
+            }
+        );
+        assert_eq!(error("min_length(8) min_length(9)").column, 15);
+        assert_eq!(
+            error("count(letter, 1, 2) & min_length(2)").kind,
+            ErrorKind::UnexpectedCharacter('&')
+        );
+        assert_eq!(error("matches(\"abc)").kind, ErrorKind::UnterminatedString);
+        assert_eq!(error("count('ab', 1, 2)").kind, ErrorKind::BadLetter);
+        assert_eq!(
+            error("count(min, 1, 2)").kind,
+            ErrorKind::Expected {
+                expected: "`letter` or a quoted letter",
+                found: "`min`".into()
+            }
+        )
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::{CharIndices, FromStr};
+use thiserror::Error;
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
+
+/// What a policy counts as one character of a password when counting letters or finding
+/// positions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Segmentation {
+    /// Unicode scalar values, i.e. `char`s.
+    #[default]
+    Scalar,
+    /// Extended grapheme clusters, what a reader perceives as one character.
+    Grapheme,
+}
+
+impl Segmentation {
+    pub const ALL: [Segmentation; 2] = [Segmentation::Scalar, Segmentation::Grapheme];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Segmentation::Scalar => "scalar",
+            Segmentation::Grapheme => "grapheme",
+        }
+    }
+
+    /// Names of all segmentations, as listed in error messages.
+    fn names() -> String {
+        Segmentation::ALL
+            .map(|segmentation| segmentation.name())
+            .join(", ")
+    }
+
+    /// Splits `text` into consecutive characters.
+    pub fn units<'a>(&self, text: &'a str) -> impl Iterator<Item = &'a str> + Clone + 'a {
+        match self {
+            Segmentation::Scalar => Units::Scalar(text.char_indices(), text),
+            Segmentation::Grapheme => Units::Grapheme(text.graphemes(true)),
+        }
+    }
+
+    /// Whether `text` continues with `letter` from the character at 1-based `position` on.
+    pub fn letter_at(&self, text: &str, letter: &str, position: usize, ignore_case: bool) -> bool {
+        match position.checked_sub(1) {
+            Some(start) => starts_with(
+                self.units(text).skip(start),
+                self.units(letter),
+                ignore_case,
+            ),
+            None => false,
+        }
+    }
+
+    /// 1-based positions of the non-overlapping occurrences of `letter` in `text`, from left to
+    /// right.
+    pub fn occurrences(&self, text: &str, letter: &str, ignore_case: bool) -> Vec<usize> {
+        let mut positions = Vec::new();
+        let length = self.units(letter).count();
+        if length == 0 {
+            return positions;
+        }
+
+        let mut rest = self.units(text);
+        let mut position = 1;
+        loop {
+            if starts_with(rest.clone(), self.units(letter), ignore_case) {
+                positions.push(position);
+                position += length;
+                rest.nth(length - 1);
+            } else if rest.next().is_some() {
+                position += 1;
+            } else {
+                return positions;
+            }
+        }
+    }
+}
+
+/// Characters of a text, split without allocating.
+#[derive(Clone)]
+enum Units<'a> {
+    Scalar(CharIndices<'a>, &'a str),
+    Grapheme(Graphemes<'a>),
+}
+
+impl<'a> Iterator for Units<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        match self {
+            Units::Scalar(chars, text) => chars
+                .next()
+                .map(|(offset, c)| &text[offset..offset + c.len_utf8()]),
+            Units::Grapheme(graphemes) => graphemes.next(),
+        }
+    }
+}
+
+/// Whether `units` start with the non-empty `prefix`.
+fn starts_with<'a, 'b>(
+    mut units: impl Iterator<Item = &'a str>,
+    prefix: impl Iterator<Item = &'b str>,
+    ignore_case: bool,
+) -> bool {
+    let mut empty = true;
+    for expected in prefix {
+        empty = false;
+        let same = match units.next() {
+            Some(unit) if ignore_case => unit
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(expected.chars().flat_map(char::to_lowercase)),
+            Some(unit) => unit == expected,
+            None => false,
+        };
+        if !same {
+            return false;
+        }
+    }
+    !empty
+}
+
+impl Display for Segmentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error(
+    "unknown segmentation {0:?}, expected one of {}",
+    Segmentation::names()
+)]
+pub struct UnknownSegmentation(pub String);
+
+impl FromStr for Segmentation {
+    type Err = UnknownSegmentation;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Segmentation::ALL
+            .iter()
+            .find(|segmentation| segmentation.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownSegmentation(name.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segment::Segmentation;
+
+    #[test]
+    fn should_split_into_scalars_or_graphemes() {
+        let text = "ae\u{301}ß";
+
+        assert_eq!(
+            Segmentation::Scalar.units(text).collect::<Vec<_>>(),
+            vec!["a", "e", "\u{301}", "ß"]
+        );
+        assert_eq!(
+            Segmentation::Grapheme.units(text).collect::<Vec<_>>(),
+            vec!["a", "e\u{301}", "ß"]
+        )
+    }
+
+    #[test]
+    fn should_find_multi_character_letters() {
+        let scalar = Segmentation::Scalar;
+
+        assert_eq!(scalar.occurrences("abababa", "aba", false), vec![1, 5]);
+        assert_eq!(scalar.occurrences("ÄäA", "ä", true), vec![1, 2]);
+        assert!(scalar.letter_at("xäbc", "äb", 2, false));
+        assert!(!scalar.letter_at("xäbc", "äb", 3, false));
+        assert!(!scalar.letter_at("xäbc", "c", 0, false));
+        assert!(!scalar.letter_at("xäbc", "cd", 4, false))
+    }
+
+    #[test]
+    fn should_parse_segmentation_names() {
+        assert_eq!("grapheme".parse(), Ok(Segmentation::Grapheme));
+        assert_eq!(
+            "byte".parse::<Segmentation>().unwrap_err().to_string(),
+            "unknown segmentation \"byte\", expected one of scalar, grapheme"
+        )
+    }
+}
@@ -0,0 +1,390 @@
+use crate::line::{Grammar, PasswordLine, PasswordLineError};
+use crate::policy::PasswordPolicy;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+/// Kind of character, each with the number of characters an attacker would try for it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    /// ASCII punctuation and space.
+    Symbol,
+    /// Anything outside ASCII.
+    Other,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 5] = [
+        CharClass::Lowercase,
+        CharClass::Uppercase,
+        CharClass::Digit,
+        CharClass::Symbol,
+        CharClass::Other,
+    ];
+
+    pub fn of(c: char) -> Self {
+        match c {
+            'a'..='z' => CharClass::Lowercase,
+            'A'..='Z' => CharClass::Uppercase,
+            '0'..='9' => CharClass::Digit,
+            ' ' => CharClass::Symbol,
+            c if c.is_ascii_punctuation() => CharClass::Symbol,
+            _ => CharClass::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Lowercase => "lowercase",
+            CharClass::Uppercase => "uppercase",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+            CharClass::Other => "other",
+        }
+    }
+
+    /// Number of characters in the class, a rough guess for non-ASCII ones.
+    pub fn pool(&self) -> usize {
+        match self {
+            CharClass::Lowercase | CharClass::Uppercase => 26,
+            CharClass::Digit => 10,
+            CharClass::Symbol => 33,
+            CharClass::Other => 100,
+        }
+    }
+
+    fn index(&self) -> usize {
+        CharClass::ALL
+            .iter()
+            .position(|class| class == self)
+            .expect("all classes are listed")
+    }
+}
+
+impl Display for CharClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Estimated bits of entropy of `password`.
+///
+/// The smaller of the brute-force estimate, length times the bits of the pool of all classes in
+/// use, and the Shannon entropy of the password's own characters, so that repeating characters
+/// does not count as random.
+pub fn strength(password: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    let mut classes = [false; 5];
+    for c in password.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+        classes[CharClass::of(c).index()] = true;
+    }
+
+    let length = password.chars().count() as f64;
+    let pool: usize = CharClass::ALL
+        .iter()
+        .filter(|class| classes[class.index()])
+        .map(CharClass::pool)
+        .sum();
+    let brute_force = length * (pool.max(1) as f64).log2();
+    let shannon: f64 = counts
+        .values()
+        .map(|&count| count as f64 * (length / count as f64).log2())
+        .sum();
+
+    brute_force.min(shannon)
+}
+
+/// Statistics over the passwords and policies of a database.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Corpus {
+    pub passwords: usize,
+    /// Number of passwords by length in characters.
+    pub lengths: BTreeMap<usize, usize>,
+    /// Number of characters by class, indexed like [`CharClass::ALL`].
+    pub classes: [usize; 5],
+    /// Number of lines by policy letter.
+    pub letters: HashMap<String, usize>,
+    /// Lowest [`strength`] of the passwords.
+    pub weakest: f64,
+    /// Highest [`strength`] of the passwords.
+    pub strongest: f64,
+    /// Sum of the [`strength`] of the passwords, see [`Corpus::mean_strength`].
+    pub total_strength: f64,
+}
+
+impl Corpus {
+    pub fn add(&mut self, line: &PasswordLine) {
+        let bits = strength(&line.password);
+        if self.passwords == 0 {
+            self.weakest = bits;
+            self.strongest = bits;
+        } else {
+            self.weakest = self.weakest.min(bits);
+            self.strongest = self.strongest.max(bits);
+        }
+        self.total_strength += bits;
+
+        self.passwords += 1;
+        *self
+            .lengths
+            .entry(line.password.chars().count())
+            .or_insert(0) += 1;
+        for c in line.password.chars() {
+            self.classes[CharClass::of(c).index()] += 1;
+        }
+        *self.letters.entry(line.letter.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn class_count(&self, class: CharClass) -> usize {
+        self.classes[class.index()]
+    }
+
+    /// The `n` letters most policies are about, ties broken alphabetically.
+    pub fn most_common_letters(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut letters: Vec<(&str, usize)> = self
+            .letters
+            .iter()
+            .map(|(letter, count)| (letter.as_str(), *count))
+            .collect();
+        letters.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        letters.truncate(n);
+        letters
+    }
+
+    pub fn mean_strength(&self) -> f64 {
+        match self.passwords {
+            0 => 0.0,
+            passwords => self.total_strength / passwords as f64,
+        }
+    }
+}
+
+impl<'a, 'b: 'a> Extend<&'a PasswordLine<'b>> for Corpus {
+    fn extend<I: IntoIterator<Item = &'a PasswordLine<'b>>>(&mut self, lines: I) {
+        for line in lines {
+            self.add(line)
+        }
+    }
+}
+
+/// Number of letters listed by [`Corpus`]'s `Display`.
+const TOP_LETTERS: usize = 5;
+
+impl Display for Corpus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} passwords", self.passwords)?;
+
+        writeln!(f, "length distribution:")?;
+        for (length, count) in &self.lengths {
+            writeln!(f, "  {:>3}  {}", length, count)?;
+        }
+
+        let characters: usize = self.classes.iter().sum();
+        writeln!(f, "character classes:")?;
+        for class in CharClass::ALL.iter() {
+            let count = self.class_count(*class);
+            let share = match characters {
+                0 => 0.0,
+                characters => 100.0 * count as f64 / characters as f64,
+            };
+            writeln!(f, "  {:<9}  {} ({:.1}%)", class.name(), count, share)?;
+        }
+
+        writeln!(f, "most common letters:")?;
+        for (letter, count) in self.most_common_letters(TOP_LETTERS) {
+            writeln!(f, "  {}  {}", letter, count)?;
+        }
+
+        writeln!(
+            f,
+            "strength: {:.1} to {:.1} bits, {:.1} on average",
+            self.weakest,
+            self.strongest,
+            self.mean_strength()
+        )
+    }
+}
+
+/// The [`strength`] of the password on one line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rating {
+    pub line: usize,
+    pub password: String,
+    pub bits: f64,
+    /// Whether the password complies with its policy.
+    pub valid: bool,
+}
+
+impl Rating {
+    /// Whether the password complies with its policy but is weaker than `min_bits`.
+    pub fn is_weak(&self, min_bits: f64) -> bool {
+        self.valid && self.bits < min_bits
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} ({:.1} bits)",
+            self.line, self.password, self.bits
+        )
+    }
+}
+
+/// Gathers the [`Corpus`] of `input` and the [`Rating`] of every readable line checked against
+/// `policy`, collecting the unreadable lines.
+pub fn survey(
+    input: &str,
+    grammar: &Grammar,
+    policy: &dyn PasswordPolicy,
+) -> (Corpus, Vec<Rating>, Vec<PasswordLineError>) {
+    let mut corpus = Corpus::default();
+    let mut ratings = Vec::new();
+    let mut errors = Vec::new();
+
+    for (number, parsed) in grammar.lines(input) {
+        match parsed {
+            Ok(line) => {
+                corpus.add(&line);
+                ratings.push(Rating {
+                    line: number,
+                    bits: strength(&line.password),
+                    valid: policy.is_valid(&line),
+                    password: line.password.into_owned(),
+                });
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+
+    (corpus, ratings, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{AOC_EXAMPLE, WITH_UNREADABLE_LINE};
+    use crate::line::{extract_password_lines, Grammar};
+    use crate::policy::Sled;
+    use crate::stats::{strength, survey, CharClass, Corpus};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn should_classify_characters() {
+        let classes: Vec<CharClass> = "aZ7 !ä".chars().map(CharClass::of).collect();
+
+        assert_eq!(
+            classes,
+            vec![
+                CharClass::Lowercase,
+                CharClass::Uppercase,
+                CharClass::Digit,
+                CharClass::Symbol,
+                CharClass::Symbol,
+                CharClass::Other
+            ]
+        )
+    }
+
+    #[test]
+    fn should_estimate_strength() {
+        assert!(close(strength("aaaaaaaa"), 0.0));
+        assert!(close(strength("abcd"), 8.0));
+        assert!(close(strength("ab"), 2.0));
+        assert!(close(strength("a1"), 2.0));
+        assert!(close(
+            strength("abcdefghijklmnopqrstuvwxyz"),
+            26.0 * 26f64.log2()
+        ));
+        assert!(strength("correct horse") < strength("Tr0ub4dor&3x"));
+        assert!(close(strength(""), 0.0))
+    }
+
+    #[test]
+    fn should_gather_corpus_statistics() {
+        let (lines, _) = extract_password_lines(AOC_EXAMPLE);
+        let mut corpus = Corpus::default();
+        corpus.extend(&lines);
+
+        assert_eq!(corpus.passwords, 3);
+        assert_eq!(
+            corpus.lengths.iter().collect::<Vec<_>>(),
+            vec![(&5, &2), (&9, &1)]
+        );
+        assert_eq!(corpus.class_count(CharClass::Lowercase), 19);
+        assert_eq!(corpus.class_count(CharClass::Digit), 0);
+        assert_eq!(corpus.most_common_letters(2), vec![("a", 1), ("b", 1)]);
+        assert!(close(corpus.weakest, 0.0));
+        assert!(close(corpus.strongest, 5f64.log2() * 5.0));
+        assert!(close(corpus.mean_strength(), 10.0 * 5f64.log2() / 3.0))
+    }
+
+    #[test]
+    fn should_print_statistics() {
+        let (lines, _) = extract_password_lines("1-3 a: abcd\n1-3 a: Ab1!\n");
+        let mut corpus = Corpus::default();
+        corpus.extend(&lines);
+
+        assert_eq!(
+            corpus.to_string(),
+            "2 passwords\n\
+             length distribution:\n    \
+             4  2\n\
+             character classes:\n  \
+             lowercase  5 (62.5%)\n  \
+             uppercase  1 (12.5%)\n  \
+             digit      1 (12.5%)\n  \
+             symbol     1 (12.5%)\n  \
+             other      0 (0.0%)\n\
+             most common letters:\n  \
+             a  2\n\
+             strength: 8.0 to 8.0 bits, 8.0 on average\n"
+        )
+    }
+
+    #[test]
+    fn should_flag_weak_valid_passwords() {
+        let input = format!("{}1-1 q: q7#Lm!xZ2v\n", WITH_UNREADABLE_LINE);
+        let (corpus, ratings, errors) = survey(&input, &Grammar::default(), &Sled::default());
+        let lines: Vec<String> = ratings
+            .iter()
+            .filter(|rating| rating.is_weak(20.0))
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(corpus.passwords, 4);
+        assert_eq!(
+            lines,
+            vec!["line 1: abcde (11.6 bits)", "line 4: ccccccccc (0.0 bits)"]
+        );
+        assert_eq!(errors.len(), 1)
+    }
+
+    #[test]
+    fn should_rate_every_readable_line() {
+        let (_, ratings, _) = survey(
+            "1-3 a: abcd\noops\n1-3 b: Ab1!\n",
+            &Grammar::default(),
+            &Sled::default(),
+        );
+        let lines: Vec<String> = ratings.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            vec!["line 1: abcd (8.0 bits)", "line 3: Ab1! (8.0 bits)"]
+        );
+        assert_eq!(
+            ratings
+                .iter()
+                .map(|rating| rating.valid)
+                .collect::<Vec<_>>(),
+            vec![true, true]
+        )
+    }
+}
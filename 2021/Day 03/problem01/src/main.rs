@@ -1,12 +1,13 @@
 use ndarray::{Array, Array2, array, ArrayBase, OwnedRepr, Dim};
 use std::ops::Deref;
 use std::str::FromStr;
-use crate::CellType::{Tree, Square};
+use crate::CellType::Tree;
 use clap::{App, Arg};
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
 enum CellType {
     Tree,
+    #[default]
     Square,
 }
 
@@ -14,7 +15,6 @@ impl FromStr for CellType {
     type Err = std::string::ParseError;
 
     fn from_str(cell: &str) -> Result<Self, Self::Err> {
-        cell.len();
         match cell {
             "." => Ok(Self::Square),
             "#" => Ok(Self::Tree),
@@ -23,12 +23,6 @@ impl FromStr for CellType {
     }
 }
 
-impl Default for CellType {
-    fn default() -> Self {
-        CellType::Square
-    }
-}
-
 #[derive(Debug, Eq, PartialEq)]
 struct Grid(Array2<CellType>);
 
@@ -49,13 +43,13 @@ impl Grid {
 #[derive(Debug)]
 struct Board {
     contents: Grid,
-    move_pattern: ArrayBase<OwnedRepr<usize>, Dim<[usize; 1]>>,
-    current_position: ArrayBase<OwnedRepr<usize>, Dim<[usize; 1]>>,
+    move_pattern: ArrayBase<OwnedRepr<isize>, Dim<[usize; 1]>>,
+    current_position: ArrayBase<OwnedRepr<isize>, Dim<[usize; 1]>>,
 }
 
 
 impl Board {
-    const START_POSITION: (usize, usize) = (0, 0);
+    const START_POSITION: (isize, isize) = (0, 0);
     pub fn new(contents: Grid, mp: MovePattern) -> Self {
 
         Board {
@@ -71,8 +65,11 @@ impl Board {
 
     pub(crate) fn calculate_collisions(&mut self) -> usize {
         let mut collisions = 0;
+        if self.move_pattern.iter().all(|step| *step == 0) {
+            return collisions;
+        }
 
-        while self.current_row() <= self.grid_rows() {
+        while self.travelled() <= self.extent() {
             self.move_position();
             if self.hit_a_tree() {
                 collisions += 1;
@@ -87,9 +84,11 @@ impl Board {
         self.current_position = array![Self::START_POSITION.0, Self::START_POSITION.1];
     }
 
+    // The forest repeats in every direction, so negative coordinates wrap around as well:
+    // row -1 is the last row, column -1 the last column.
     fn hit_a_tree(&self) -> bool {
-        let row = self.current_row() % self.grid_rows();
-        let col = self.current_col() % self.grid_cols();
+        let row = self.current_row().rem_euclid(self.grid_rows() as isize) as usize;
+        let col = self.current_col().rem_euclid(self.grid_cols() as isize) as usize;
         match self.contents.cell_at(row, col) {
             None => panic!("Position does not exist m("),
             Some(cell) if *cell == Tree => true,
@@ -101,14 +100,36 @@ impl Board {
         self.current_position += &self.move_pattern;
     }
 
-    fn current_row(&self) -> usize {
+    fn current_row(&self) -> isize {
         *self.current_position.get(0).unwrap()
     }
 
-    fn current_col(&self) -> usize {
+    fn current_col(&self) -> isize {
         *self.current_position.get(1).unwrap()
     }
 
+    fn moves_vertically(&self) -> bool {
+        *self.move_pattern.get(0).unwrap() != 0
+    }
+
+    // The trip ends once the sled has come further than the grid is high, or wide for
+    // horizontal patterns, no matter in which direction it goes.
+    fn travelled(&self) -> usize {
+        if self.moves_vertically() {
+            self.current_row().unsigned_abs()
+        } else {
+            self.current_col().unsigned_abs()
+        }
+    }
+
+    fn extent(&self) -> usize {
+        if self.moves_vertically() {
+            self.grid_rows()
+        } else {
+            self.grid_cols()
+        }
+    }
+
     fn grid_rows(&self) -> usize {
         self.contents.nrows()
     }
@@ -180,15 +201,16 @@ impl From<&str> for Grid {
     fn from(input: &str) -> Self {
         match Grid::from_str(input) {
             Ok(result) => result,
-            Err(error) => panic!(error),
+            Err(error) => panic!("{}", error),
         }
     }
 }
 
+// Negative rows move up, negative cols move left.
 #[derive(Copy, Clone, Debug, PartialEq)]
 struct MovePattern {
-    rows: usize,
-    cols: usize,
+    rows: isize,
+    cols: isize,
 }
 
 impl Default for MovePattern {
@@ -197,6 +219,23 @@ impl Default for MovePattern {
     }
 }
 
+impl FromStr for MovePattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a pattern like `rows,cols`, got {:?}", pattern);
+        let steps: Vec<isize> = pattern
+            .split(',')
+            .map(|step| step.trim().parse::<isize>().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+
+        match steps.as_slice() {
+            [rows, cols] => Ok(MovePattern { rows: *rows, cols: *cols }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
 fn main() {
     let matches = App::new("AOC Day 3")
         .about("Calculates collisions in a forest")
@@ -207,19 +246,31 @@ fn main() {
                 .takes_value(true)
                 .required(true)
                 .about("the input file"))
+        .arg(
+            Arg::new("pattern")
+                .short('p')
+                .long("pattern")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .allow_hyphen_values(true)
+                .validator(MovePattern::from_str)
+                .about("a move pattern `rows,cols`, negative values move up or left"))
         .get_matches();
 
     let input_file = matches.value_of("file").unwrap();
     let content = std::fs::read_to_string(input_file).expect("Could not open file");
 
     let mut board = Board::from_str(content.as_str()).expect("Could not init Board");
-    let patterns = [
-        MovePattern { rows: 1, cols: 1 },
-        MovePattern { rows: 1, cols: 3 },
-        MovePattern { rows: 1, cols: 5 },
-        MovePattern { rows: 1, cols: 7 },
-        MovePattern { rows: 2, cols: 1 },
-    ];
+    let patterns = match matches.values_of_t::<MovePattern>("pattern") {
+        Ok(patterns) => patterns,
+        Err(_) => vec![
+            MovePattern { rows: 1, cols: 1 },
+            MovePattern { rows: 1, cols: 3 },
+            MovePattern { rows: 1, cols: 5 },
+            MovePattern { rows: 1, cols: 7 },
+            MovePattern { rows: 2, cols: 1 },
+        ],
+    };
     let mut mult_result = 1;
     println!("Trying the following patterns:");
     for pattern in patterns.iter() {
@@ -287,6 +338,8 @@ mod tests {
         input
     }
 
+    // test-case expands panicking cases into functions ending in `()`.
+    #[allow(clippy::unused_unit)]
     #[test_case("-" => panics "unrecognized cell value \"-\"")]
     // #[test_case("/" => panics "unrecognized cell value /")] # Note: Due to code generation,
     // this would lead to a duplicated test name, all special signs leading to an exception need to
@@ -308,6 +361,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unused_unit)]
     fn should_build_board_with_move_pattern() {
         let grid = given_aoc_grid();
         when_initializing_board(grid, AOC_MOVE_PATTERN);
@@ -322,6 +376,34 @@ mod tests {
         assert_eq!(trees_hit, 7)
     }
 
+    #[test_case(1, 1 => 2)]
+    #[test_case(1, 3 => 7)]
+    #[test_case(1, 5 => 3)]
+    #[test_case(1, 7 => 4)]
+    #[test_case(2, 1 => 2)]
+    fn should_count_aoc_collisions(rows: isize, cols: isize) -> usize {
+        let board = when_initializing_board(given_aoc_grid(), MovePattern { rows, cols });
+        when_calculating_collisions(board)
+    }
+
+    #[test_case(1, -1 => 2; "down and left")]
+    #[test_case(-1, 0 => 2; "up")]
+    #[test_case(0, 1 => 2; "right")]
+    #[test_case(-1, -1 => 0; "up and left")]
+    #[test_case(0, 0 => 0; "standing still")]
+    fn should_wrap_negative_coordinates(rows: isize, cols: isize) -> usize {
+        let grid = when_initializing_grid(given_input(".#.\n..#\n#.."));
+        let board = when_initializing_board(grid, MovePattern { rows, cols });
+        when_calculating_collisions(board)
+    }
+
+    #[test]
+    fn should_parse_signed_move_pattern() {
+        assert_eq!(MovePattern::from_str("-1, 3"), Ok(MovePattern { rows: -1, cols: 3 }));
+        assert!(MovePattern::from_str("1").is_err());
+        assert!(MovePattern::from_str("1,x").is_err())
+    }
+
     #[test]
     fn should_add_to_position() {
         let mut left = array![0,0];